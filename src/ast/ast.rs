@@ -2,9 +2,18 @@ use derive_more::{Add, AsMut, AsRef, Deref, DerefMut, Display, From, Mul, Sub};
 
 use pest::iterators::{Pair, Pairs};
 use pest::prec_climber::{Assoc, Operator, PrecClimber};
-
-
-use super::operator::{is_binop, BinOp, UnOp};
+use num_bigint::{BigInt, Sign};
+use num_traits::ToPrimitive;
+
+
+use super::class::{Class, Instance};
+use super::iter::Iter;
+use super::list::{self, List};
+use super::map::{self, Map};
+use super::set::{self, Set};
+use super::literal::{self, Fragment};
+use super::operator::{is_binop, is_compound_assign, BinOp, Fixity, UnOp};
+use super::range::Range;
 use super::function::{LoxFn, BuiltinFn, UserFn};
 use crate::error::Error;
 use crate::parser::Rule;
@@ -68,14 +77,31 @@ impl Ident {
 #[derive(Clone, PartialEq, PartialOrd, Display)]
 pub enum Object {
     Int(isize),
-    Float(f32),
+    /// An int outside of `isize`'s range. Smaller results are always `Int`s.
+    BigInt(BigInt),
+    /// Printed in its shortest round-trip form, always with a `.` or exponent.
+    #[display(fmt = "{:?}", _0)]
+    Float(f64),
     Str(String),
     Ident(Ident),
     Bool(bool),
     #[display(fmt = "<func {}>", "_0.borrow().name()")]
     Func(Func),
-    #[display(fmt = "()")]
-    Unit,
+    #[display(fmt = "{}", "_0.name")]
+    Class(Class),
+    #[display(fmt = "{} instance", "_0.borrow().class.name")]
+    Instance(Instance),
+    #[display(fmt = "{}", "list::display(_0)")]
+    List(List),
+    #[display(fmt = "{}", "map::display(_0)")]
+    Map(Map),
+    #[display(fmt = "{}", "set::display(_0)")]
+    Set(Set),
+    Range(Range),
+    #[display(fmt = "<iterator>")]
+    Iter(Iter),
+    #[display(fmt = "nil")]
+    Nil,
 }
 
 impl fmt::Debug for Object {
@@ -83,39 +109,72 @@ impl fmt::Debug for Object {
         match self {
             Object::Func(func) => write!(f, "<func {}({})>", func.borrow().name(), func.borrow().arity()),
             Object::Int(e) => write!(f, "{:?}", e),
+            Object::BigInt(e) => write!(f, "{:?}", e),
             Object::Float(e) => write!(f, "{:?}", e),
             Object::Str(e) => write!(f, "{:?}", e),
             Object::Ident(e) => write!(f, "{:?}", e),
             Object::Bool(e) => write!(f, "{:?}", e),
-            Object::Unit => write!(f, "()"),
+            Object::Class(class) => write!(f, "<class {}>", class.name),
+            Object::Instance(instance) => write!(f, "<{} instance>", instance.borrow().class.name),
+            Object::List(list) => write!(f, "{}", list::display(list)),
+            Object::Map(map) => write!(f, "{}", map::display(map)),
+            Object::Set(set) => write!(f, "{}", set::display(set)),
+            Object::Range(range) => write!(f, "{}", range),
+            Object::Iter(_) => write!(f, "<iterator>"),
+            Object::Nil => write!(f, "nil"),
         }
     }
 }
 
 impl Default for Object {
     fn default() -> Self {
-        Object::Unit
+        Object::Nil
     }
 }
 
 impl Object {
     pub fn from_pair(pair: &Pair<Rule>) -> Self {
         match pair.as_rule() {
-            Rule::int => Object::Int(pair.as_str().parse().unwrap()),
-            Rule::float => Object::Float(pair.as_str().parse().unwrap()),
+            Rule::int => literal::parse_int(pair).expect("int literals are checked by the parser"),
+            Rule::float => literal::parse_float(pair).expect("float literals are checked by the parser"),
             Rule::ident => Object::Ident(Ident(pair.as_str().to_string())),
-            Rule::string => Object::Str(pair.as_str()[1..pair.as_str().len() - 1].into()),
             _ => todo!(),
         }
     }
 
+    /// The name of this value's type, for error messages.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Object::Int(_) | Object::BigInt(_) => "int",
+            Object::Float(_) => "float",
+            Object::Str(_) => "string",
+            Object::Ident(_) => "identifier",
+            Object::Bool(_) => "bool",
+            Object::Func(_) => "function",
+            Object::Class(_) => "class",
+            Object::Instance(_) => "instance",
+            Object::List(_) => "list",
+            Object::Map(_) => "map",
+            Object::Set(_) => "set",
+            Object::Range(_) => "range",
+            Object::Iter(_) => "iterator",
+            Object::Nil => "nil",
+        }
+    }
+
     pub fn is_truthy(&self) -> Result<bool, Error> {
         match self {
             Object::Bool(b) => Ok(*b),
             Object::Int(i) => Ok(*i > 0),
+            Object::BigInt(i) => Ok(i.sign() == Sign::Plus),
             Object::Float(f) => Ok(*f > 0.0),
             Object::Str(s) => Ok(!s.is_empty()),
-            Object::Unit => Ok(false),
+            Object::List(list) => Ok(!list.borrow().is_empty()),
+            Object::Map(map) => Ok(!map.borrow().is_empty()),
+            Object::Set(set) => Ok(!set.borrow().is_empty()),
+            Object::Range(range) => Ok(range.first().is_some()),
+            Object::Class(_) | Object::Instance(_) | Object::Iter(_) => Ok(true),
+            Object::Nil => Ok(false),
             _ => Err(Error::UnsupportedTruthiness("".into())),
         }
     }
@@ -126,9 +185,24 @@ pub enum Expr {
     Object(Object),
     UnOp(UnOp, Box<Expr>),
     BinOp(Box<Expr>, BinOp, Box<Expr>),
-    Access(Box<Expr>, Box<Expr>),
+    Access(Box<Expr>, Ident),
     Assign(Box<Expr>, Box<Expr>),
-    Call(Ident, Vec<Expr>),
+    /// `a += b`, evaluating the target once.
+    CompoundAssign(Box<Expr>, BinOp, Box<Expr>),
+    /// `++a` or `a--`, where the operator is `Plus` or `Minus`.
+    Increment(Box<Expr>, BinOp, Fixity),
+    Call(Box<Expr>, Vec<Expr>),
+    Lambda(Vec<Ident>, Block),
+    /// `[a, b, c]`
+    List(Vec<Expr>),
+    /// `{key: value}`
+    Map(Vec<(Expr, Expr)>),
+    /// `list[index]` or `map[key]`
+    Index(Box<Expr>, Box<Expr>),
+    /// `list[start:end]`, where either bound may be left out.
+    Slice(Box<Expr>, Option<Box<Expr>>, Option<Box<Expr>>),
+    /// `"a ${b} c"`, each piece is printed and joined at runtime.
+    Interpolation(Vec<Expr>),
 }
 
 impl Expr {
@@ -137,69 +211,126 @@ impl Expr {
     }
 
     pub fn from_pair(pair: &Pair<Rule>) -> Self {
-        PREC_CLIMBER.climb(pair.clone().into_inner(), Expr::primary, Expr::infix)
+        PREC_CLIMBER.climb(pair.clone().into_inner(), Operand::from_pair, Operand::infix).apply()
     }
 
-    fn handle_term(pair: &Pair<Rule>) -> Expr {
+    fn handle_term(pair: &Pair<Rule>) -> Operand {
         let pairs: Vec<Pair<Rule>> = pair.clone().into_inner().collect();
+        let value = pairs
+            .iter()
+            .position(|p| p.as_rule() == Rule::value)
+            .unwrap();
+        let (unary, rest) = pairs.split_at(value);
+
+        // Postfix operators bind tighter than prefix ones: `-a.b()` is `-(a.b())`
+        let operand = rest[1..].iter().fold(Expr::primary(rest[0].clone()), |inner, postfix| {
+            match postfix.as_rule() {
+                Rule::call => {
+                    let args: Vec<Expr> = postfix.clone().into_inner().map(|p| Expr::from_pair(&p)).collect();
+                    Expr::Call(Box::new(inner), args)
+                }
+                Rule::access => {
+                    let name = Ident::from_pair(&postfix.clone().into_inner().next().unwrap());
+                    Expr::Access(Box::new(inner), name)
+                }
+                Rule::index => {
+                    let index = postfix.clone().into_inner().next().unwrap();
+                    Expr::Index(Box::new(inner), Box::new(Expr::from_pair(&index)))
+                }
+                Rule::slice => {
+                    let bounds: Vec<Pair<Rule>> = postfix.clone().into_inner().collect();
+                    let colon = bounds.iter().position(|p| p.as_rule() == Rule::colon).unwrap();
+                    let bound = |p: Option<&Pair<Rule>>| p.map(|p| Box::new(Expr::from_pair(p)));
 
-        if pairs[0].as_rule() == Rule::call {
-            let pairs: Vec<Pair<_>> = pairs[0].clone().into_inner().collect();
-            let ident = Ident::from_pair(&pairs[0]);
-            let args: Vec<Expr> = pairs.iter().skip(1).map(Expr::from_pair).collect();
-
-            return Expr::Call(ident, args);
-        }
-        // println!("pairs: {:?}", pairs);
-        
-        match &pairs[..] {
-            [unary @ .., rhs] => {
-                unary.iter().fold(Expr::from_pair(rhs), |inner: Expr, op| {
-                    match op.as_rule() {
-                        Rule::op_unary_not => Expr::UnOp(UnOp::Not, Box::new(inner)),
-                        Rule::op_unary_minus => Expr::UnOp(UnOp::Minus, Box::new(inner)),
-                        _ => unreachable!(),
-                    }
-                })
+                    Expr::Slice(Box::new(inner), bound(bounds[..colon].first()), bound(bounds.get(colon + 1)))
+                }
+                Rule::op_increment | Rule::op_decrement => {
+                    Expr::Increment(Box::new(inner), BinOp::from(postfix.as_rule()), Fixity::Postfix)
+                }
+                _ => unreachable!(),
             }
-            [] => unreachable!(),
-        }
+        });
+
+        // `++` and `--` apply to their target straight away, while the plain
+        // unary operators before them are held back for `**`
+        let held = unary
+            .iter()
+            .position(|op| op.as_rule() == Rule::op_increment || op.as_rule() == Rule::op_decrement)
+            .unwrap_or(unary.len());
+        let (held, increments) = unary.split_at(held);
+
+        let expr = increments.iter().rev().fold(operand, |inner: Expr, op| {
+            Expr::Increment(Box::new(inner), BinOp::from(op.as_rule()), Fixity::Prefix)
+        });
+
+        let prefix = held
+            .iter()
+            .map(|op| match op.as_rule() {
+                Rule::op_unary_not => UnOp::Not,
+                Rule::op_unary_minus => UnOp::Minus,
+                Rule::op_unary_tilde => UnOp::Tilde,
+                _ => unreachable!(),
+            })
+            .collect();
+
+        Operand { expr, prefix }
     }
 
     fn primary(pair: Pair<Rule>) -> Expr {
         match pair.as_rule() {
-            Rule::float | Rule::int | Rule::string => Object::from_pair(&pair).into(),
+            Rule::float | Rule::int => Object::from_pair(&pair).into(),
+            Rule::string => {
+                let fragments = literal::parse_string(&pair)
+                    .expect("string literals are checked by the parser");
+
+                if fragments.iter().all(|f| matches!(f, Fragment::Text(_))) {
+                    let text = fragments.into_iter().map(|f| match f {
+                        Fragment::Text(text) => text,
+                        Fragment::Code(_) => unreachable!(),
+                    });
+
+                    return Expr::Object(Object::Str(text.collect()));
+                }
+
+                let parts = fragments.into_iter().map(|f| match f {
+                    Fragment::Text(text) => Expr::Object(Object::Str(text)),
+                    Fragment::Code(code) => Expr::from_pair(&code),
+                });
+
+                Expr::Interpolation(parts.collect())
+            }
             Rule::ident => Object::Ident(Ident(pair.as_str().into())).into(),
             Rule::value => Expr::primary(pair.into_inner().next().unwrap()),
-            Rule::term => Expr::handle_term(&pair),
+            Rule::term => Expr::handle_term(&pair).apply(),
             Rule::rtrue => Expr::Object(Object::from(true)),
             Rule::rfalse => Expr::Object(Object::from(false)),
+            Rule::rnil => Expr::Object(Object::Nil),
+            Rule::list => Expr::List(pair.into_inner().map(|p| Expr::from_pair(&p)).collect()),
+            Rule::map => {
+                let entries = pair.into_inner().map(|entry| {
+                    let mut inner = entry.into_inner();
+                    let key = Expr::from_pair(&inner.next().unwrap());
+                    (key, Expr::from_pair(&inner.next().unwrap()))
+                });
+
+                Expr::Map(entries.collect())
+            }
+            Rule::lambda => {
+                let pairs: Vec<Pair<Rule>> = pair.into_inner().collect();
+                let parameters: Vec<Ident> = if pairs.len() == 2 {
+                    pairs[0].clone().into_inner().map(|p| Ident::from_pair(&p)).collect()
+                } else {
+                    vec![]
+                };
+
+                Expr::Lambda(parameters, Block::from_pair(pairs.last().unwrap()))
+            }
             Rule::expr => Expr::from_pair(&pair),
             Rule::op_unary_not | Rule::op_unary_minus => {
                 // let rhs = Expr::primary()
                 println!("Not Pair: {:?}", pair);
                 todo!()
             }
-            Rule::call => {
-                let pairs: Vec<Pair<Rule>> = pair.into_inner().collect();
-
-                let ident = Ident(
-                    pairs
-                        .first()
-                        .into_iter()
-                        .next()
-                        .unwrap()
-                        .as_str()
-                        .to_string(),
-                );
-                let args: Vec<Expr> = pairs
-                    .iter()
-                    .skip(1)
-                    .map(|p| Expr::from_pair(p))
-                    .collect();
-
-                Expr::Call(ident, args)
-            }
             _ => {
                 println!("{:?}", pair.as_rule());
                 todo!()
@@ -210,8 +341,8 @@ impl Expr {
     fn infix(lhs: Expr, op: Pair<Rule>, rhs: Expr) -> Expr {
         match op.as_rule() {
             o if is_binop(o) => Expr::binop(lhs, BinOp::from(o), rhs),
-            Rule::op_dot => Expr::Access(Box::new(lhs), Box::new(rhs)),
             Rule::op_assign => Expr::Assign(Box::new(lhs), Box::new(rhs)),
+            o if is_compound_assign(o) => Expr::CompoundAssign(Box::new(lhs), BinOp::from(o), Box::new(rhs)),
             other => {
                 println!("{:?}", other);
                 todo!()
@@ -220,6 +351,47 @@ impl Expr {
     }
 }
 
+/// One side of an infix operator while climbing. The unary operators in front
+/// of a term are kept apart until the term meets a looser operator than `**`, so
+/// `-2 ** 2` is `-(2 ** 2)`.
+struct Operand {
+    expr: Expr,
+    /// Outermost first.
+    prefix: Vec<UnOp>,
+}
+
+impl Operand {
+    fn from_pair(pair: Pair<Rule>) -> Self {
+        match pair.as_rule() {
+            Rule::term => Expr::handle_term(&pair),
+            _ => Operand {
+                expr: Expr::primary(pair),
+                prefix: Vec::new(),
+            },
+        }
+    }
+
+    fn infix(lhs: Operand, op: Pair<Rule>, rhs: Operand) -> Operand {
+        match op.as_rule() {
+            Rule::op_power => Operand {
+                expr: Expr::binop(lhs.expr, BinOp::Pow, rhs.apply()),
+                prefix: lhs.prefix,
+            },
+            _ => Operand {
+                expr: Expr::infix(lhs.apply(), op, rhs.apply()),
+                prefix: Vec::new(),
+            },
+        }
+    }
+
+    fn apply(self) -> Expr {
+        self.prefix
+            .into_iter()
+            .rev()
+            .fold(self.expr, |inner, op| Expr::UnOp(op, Box::new(inner)))
+    }
+}
+
 #[derive(Default, Debug, Clone, PartialEq, PartialOrd, AsRef, AsMut, Deref, DerefMut)]
 pub struct Block(pub Vec<Decl>);
 
@@ -228,6 +400,28 @@ impl Block {
         let inner_decls: Vec<Decl> = pair.clone().into_inner().map(|p| Decl::from_pair(&p)).collect();
         Self(inner_decls)
     }
+
+    /// Whether this is the body of a generator.
+    pub fn yields(&self) -> bool {
+        self.0.iter().any(|Decl::Stmt(stmt)| stmt.yields())
+    }
+
+    /// Build the body of an `if`, `while`, or `for`, which is either a `block` or,
+    /// in the standard dialect, any single `statement`.
+    pub fn from_body(pair: &Pair<Rule>) -> Self {
+        match pair.as_rule() {
+            Rule::statement => {
+                let inner = pair.clone().into_inner().next().unwrap();
+
+                if inner.as_rule() == Rule::block {
+                    Block::from_pair(&inner)
+                } else {
+                    Block(vec![Decl::Stmt(Stmt::from_inner(&inner))])
+                }
+            }
+            _ => Block::from_pair(pair),
+        }
+    }
 }
 
 #[derive(Clone, PartialEq, PartialOrd)]
@@ -237,21 +431,40 @@ pub enum Stmt {
     Block(Block),
     VarDecl(Ident, Option<Expr>),
     If(Expr, Block, Block),
-    While(Expr, Block),
+    While(Option<Ident>, Expr, Block, Option<Expr>),
+    DoWhile(Option<Ident>, Block, Expr),
+    /// `for (item in iterable) body`
+    ForIn(Option<Ident>, Ident, Expr, Block),
+    Break(Option<Ident>),
+    Continue(Option<Ident>),
     Func(Ident, Func),
+    Class(Ident, Option<Ident>, Vec<Func>),
     Return(Option<Expr>),
+    Yield(Expr),
 }
 
 impl fmt::Debug for Stmt {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Stmt::Func(_, func) => write!(f, "<func {}({})>", func.borrow().name(), func.borrow().arity()),
+            Stmt::Class(name, Some(superclass), methods) => {
+                write!(f, "<class {} < {} ({} methods)>", name, superclass, methods.len())
+            }
+            Stmt::Class(name, None, methods) => write!(f, "<class {} ({} methods)>", name, methods.len()),
             Stmt::Expr(e) | Stmt::Print(e) => write!(f, "{:?}", e),
             Stmt::Block(b) => write!(f, "{:?}", b),
             Stmt::VarDecl(i, e) => write!(f, "{:?} = {:?}", i, e),
             Stmt::If(c, g, b) => write!(f, "[if] {:?} {{ {:?} }} else {{ {:?} }}", c, g, b),
-            Stmt::While(e, b) => write!(f, "[while] {:?} {{ {:?} }}", e, b),
+            Stmt::While(Some(l), e, b, i) => write!(f, "{}: [while] {:?} {{ {:?} }} [inc] {:?}", l, e, b, i),
+            Stmt::While(None, e, b, i) => write!(f, "[while] {:?} {{ {:?} }} [inc] {:?}", e, b, i),
+            Stmt::DoWhile(Some(l), b, e) => write!(f, "{}: [do] {{ {:?} }} while {:?}", l, b, e),
+            Stmt::DoWhile(None, b, e) => write!(f, "[do] {{ {:?} }} while {:?}", b, e),
+            Stmt::ForIn(Some(l), v, e, b) => write!(f, "{}: [for] {} in {:?} {{ {:?} }}", l, v, e, b),
+            Stmt::ForIn(None, v, e, b) => write!(f, "[for] {} in {:?} {{ {:?} }}", v, e, b),
+            Stmt::Break(l) => write!(f, "[break] {:?}", l),
+            Stmt::Continue(l) => write!(f, "[continue] {:?}", l),
             Stmt::Return(e) => write!(f, "[return] {:?}", e),
+            Stmt::Yield(e) => write!(f, "[yield] {:?}", e),
         }
     }
 }
@@ -261,9 +474,15 @@ impl Stmt {
         // println!("[stmt] {:?}", pair.as_rule());
 
         let pair = pair.clone().into_inner().next().unwrap();
+        Stmt::from_inner(&pair)
+    }
+
+    /// Build a statement from one of the alternatives of the `statement` rule.
+    fn from_inner(pair: &Pair<Rule>) -> Self {
+        let pair = pair.clone();
 
         match pair.as_rule() {
-            Rule::statement => Stmt::from_pair(pair.clone().into_inner().next().as_ref().unwrap()),
+            Rule::statement => Stmt::from_pair(&pair),
             Rule::expr_stmt => {
                 // println!("[expr]");
                 let inner_expr = pair.clone().into_inner().next().unwrap();
@@ -279,48 +498,85 @@ impl Stmt {
             Rule::var_decl => {
                 let pairs: Vec<Pair<Rule>> = pair.clone().into_inner().collect();
                 let ident = Ident(pairs[0].as_str().to_string());
-                let initializer = pairs.last().map(|p| Expr::from_pair(p));
+                let initializer = pairs.get(1).map(|p| Expr::from_pair(p));
 
                 Stmt::VarDecl(ident, initializer)
             }
             Rule::while_stmt => {
                 let pairs: Vec<Pair<Rule>> = pair.clone().into_inner().collect();
                 let pred = Expr::from_pair(&pairs[0]);
-                let block = Block::from_pair(&pairs[1]);
+                let block = Block::from_body(&pairs[1]);
 
-                Stmt::While(pred, block)
+                Stmt::While(None, pred, block, None)
             }
-            Rule::for_stmt => {
-                let pairs: Vec<Pair<Rule>> = pair.clone().into_inner().collect();
-                let rules: Vec<Rule> = pairs.iter().map(|p| p.as_rule()).collect();
+            Rule::loop_stmt => {
+                let block = Block::from_pair(&pair.into_inner().next().unwrap());
+                Stmt::While(None, Object::from(true).into(), block, None)
+            }
+            Rule::do_while_stmt => {
+                let pairs: Vec<Pair<Rule>> = pair.into_inner().collect();
+                let block = Block::from_pair(&pairs[0]);
+                let pred = Expr::from_pair(&pairs[1]);
 
-                let var_decl;
-                let pred;
-                let inc;
+                Stmt::DoWhile(None, block, pred)
+            }
+            Rule::labeled_stmt => {
+                let pairs: Vec<Pair<Rule>> = pair.into_inner().collect();
+                let label = Ident::from_pair(&pairs[0]);
 
-                match &rules[..] {
-                    [Rule::var_decl, Rule::expr, Rule::semi, Rule::expr, Rule::block] => {
-                        let var_pairs: Vec<Pair<Rule>> = pairs[0].clone().into_inner().collect();
-                        let ident = Ident(var_pairs[0].as_str().to_string());
-                        let initializer = var_pairs.last().map(Expr::from_pair);
+                let mut stmt = Stmt::from_inner(&pairs[1]);
+                stmt.set_label(label);
+                stmt
+            }
+            Rule::break_stmt => {
+                let label = pair.into_inner().find(|p| p.as_rule() == Rule::ident);
+                Stmt::Break(label.as_ref().map(Ident::from_pair))
+            }
+            Rule::continue_stmt => {
+                let label = pair.into_inner().find(|p| p.as_rule() == Rule::ident);
+                Stmt::Continue(label.as_ref().map(Ident::from_pair))
+            }
+            Rule::for_in_stmt => {
+                let pairs: Vec<Pair<Rule>> = pair.into_inner().filter(|p| p.as_rule() != Rule::kw_var).collect();
+                let item = Ident::from_pair(&pairs[0]);
+                let iterable = Expr::from_pair(&pairs[2]);
+                let block = Block::from_body(&pairs[3]);
 
-                        var_decl = Stmt::VarDecl(ident, initializer);
-                        pred = Expr::from_pair(&pairs[1]);
-                        inc = Expr::from_pair(&pairs[3]);
+                Stmt::ForIn(None, item, iterable, block)
+            }
+            Rule::for_stmt => {
+                // for (init; pred; inc) body
+                // becomes
+                // { init; while pred { body } (then inc after every iteration) }
+                let mut pairs = pair.clone().into_inner().peekable();
+
+                let init = match pairs.next() {
+                    Some(p) if p.as_rule() == Rule::var_decl => Some(Stmt::from_inner(&p)),
+                    Some(p) if p.as_rule() == Rule::expr => {
+                        pairs.next(); // semi
+                        Some(Stmt::Expr(Expr::from_pair(&p)))
                     }
-                    _ => todo!(),
-                }
+                    _ => None,
+                };
 
-                let mut block = Block::from_pair(pairs.last().unwrap());
-                block.0.push(Decl::Stmt(Stmt::Expr(inc)));
+                let pred = match pairs.peek() {
+                    Some(p) if p.as_rule() == Rule::expr => Expr::from_pair(&pairs.next().unwrap()),
+                    _ => Object::from(true).into(),
+                };
+                pairs.next(); // semi
 
-                let while_stmt = Stmt::While(pred, block);
+                let inc = match pairs.peek() {
+                    Some(p) if p.as_rule() == Rule::expr => Some(Expr::from_pair(&pairs.next().unwrap())),
+                    _ => None,
+                };
 
-                let mut desugared = Block::default();
-                desugared.0.push(Decl::Stmt(var_decl));
-                desugared.0.push(Decl::Stmt(while_stmt));
+                let block = Block::from_body(&pairs.next().unwrap());
+                let while_stmt = Stmt::While(None, pred, block, inc);
 
-                Stmt::Block(desugared)
+                match init {
+                    Some(init) => Stmt::Block(Block(vec![Decl::Stmt(init), Decl::Stmt(while_stmt)])),
+                    None => while_stmt,
+                }
             }
             Rule::if_stmt => {
                 let pairs: Vec<Pair<Rule>> = pair.into_inner().collect();
@@ -328,13 +584,13 @@ impl Stmt {
                 match &pairs[..] {
                     [pred, good, bad] => {
                         let pred = Expr::from_pair(pred);
-                        let good = Block::from_pair(good);
-                        let bad = Block::from_pair(bad);
+                        let good = Block::from_body(good);
+                        let bad = Block::from_body(bad);
                         Stmt::If(pred, good, bad)
                     }
                     [pred, good] => {
                         let pred = Expr::from_pair(pred);
-                        let good = Block::from_pair(good);
+                        let good = Block::from_body(good);
                         let bad = Block::default();
 
                         Stmt::If(pred, good, bad)
@@ -343,8 +599,12 @@ impl Stmt {
                 }
             }
             Rule::return_stmt => {
-                let expr = pair.into_inner().next().map(|e| Expr::from_pair(&e));
-                Stmt::Return(expr)
+                let expr = pair.into_inner().find(|p| p.as_rule() == Rule::expr);
+                Stmt::Return(expr.as_ref().map(Expr::from_pair))
+            }
+            Rule::yield_stmt => {
+                let expr = pair.into_inner().find(|p| p.as_rule() == Rule::expr).unwrap();
+                Stmt::Yield(Expr::from_pair(&expr))
             }
             Rule::block => {
                 // let inner_decls: Vec<Decl> = pair.into_inner().map(Decl::from_pair).collect();
@@ -356,6 +616,33 @@ impl Stmt {
             }
         }
     }
+
+    /// Whether running this statement can reach a `yield`. Functions declared
+    /// inside it don't count, since they're generators of their own.
+    fn yields(&self) -> bool {
+        match self {
+            Stmt::Yield(_) => true,
+            Stmt::Block(block)
+            | Stmt::While(_, _, block, _)
+            | Stmt::DoWhile(_, block, _)
+            | Stmt::ForIn(_, _, _, block) => block.yields(),
+            Stmt::If(_, good, bad) => good.yields() || bad.yields(),
+            _ => false,
+        }
+    }
+
+    /// Attach a label to a loop. A desugared `for` loop is a block ending in its `while`.
+    fn set_label(&mut self, label: Ident) {
+        match self {
+            Stmt::While(l, _, _, _) | Stmt::DoWhile(l, _, _) | Stmt::ForIn(l, _, _, _) => *l = Some(label),
+            Stmt::Block(block) => {
+                if let Some(Decl::Stmt(stmt)) = block.0.last_mut() {
+                    stmt.set_label(label);
+                }
+            }
+            _ => (),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
@@ -373,23 +660,26 @@ impl Decl {
                 Self::from(stmt)
             }
             Rule::fun_decl => {
-                let pairs: Vec<Pair<Rule>> = pair.into_inner().next().unwrap().into_inner().collect();
+                let user_fn = UserFn::from_pair(&pair.into_inner().next().unwrap());
+                let func_name = user_fn.name.clone();
 
-                let func_name: Ident = Ident::from_pair(&pairs[0]);
-                let parameters: Vec<Ident> = if pairs.len() == 3 {
-                    pairs[1].clone().into_inner().map(|p| Ident::from_pair(&p)).collect()
-                } else {
-                    vec![]
-                };
+                Decl::Stmt(Stmt::Func(func_name, Rc::new(RefCell::new(Box::new(user_fn)))))
+            }
+            Rule::class_decl => {
+                let pairs: Vec<Pair<Rule>> = pair.into_inner().collect();
 
-                let body = if pairs.len() == 3 { 
-                    Block::from_pair(&pairs[2])
-                } else {
-                    Block::from_pair(&pairs[1])    
-                };
+                let class_name = Ident::from_pair(&pairs[0]);
+                let superclass = pairs
+                    .get(1)
+                    .filter(|p| p.as_rule() == Rule::ident)
+                    .map(Ident::from_pair);
+                let methods: Vec<Func> = pairs
+                    .iter()
+                    .filter(|p| p.as_rule() == Rule::function)
+                    .map(|p| -> Func { Rc::new(RefCell::new(Box::new(UserFn::from_pair(p)))) })
+                    .collect();
 
-                let user_fn = UserFn::new(func_name.clone(), parameters, Default::default(), body);
-                Decl::Stmt(Stmt::Func(func_name, Rc::new(RefCell::new(Box::new(user_fn)))))
+                Decl::Stmt(Stmt::Class(class_name, superclass, methods))
             }
             _ => {
                 println!("{:?}, {:#?}", pair.as_rule(), pair);
@@ -436,7 +726,7 @@ impl Program {
 
 impl_from!(
     isize > Object::Int,
-    f32 > Object::Float,
+    f64 > Object::Float,
     String > Object::Str,
     Ident > Object::Ident,
     // Path > Object::Path,
@@ -446,18 +736,41 @@ impl_from!(
     Stmt > Decl::Stmt,
 );
 
+impl From<BigInt> for Object {
+    fn from(int: BigInt) -> Self {
+        match int.to_isize() {
+            Some(small) => Object::Int(small),
+            None => Object::BigInt(int),
+        }
+    }
+}
+
 impl_try_from!(
     isize as Object::Int,
-    f32 as Object::Float,
+    BigInt as Object::BigInt,
+    f64 as Object::Float,
     String as Object::Str,
     Ident as Object::Ident,
     bool as Object::Bool,
     Func as Object::Func,
+    Class as Object::Class,
+    Instance as Object::Instance,
+    List as Object::List,
+    Map as Object::Map,
+    Set as Object::Set,
+    Range as Object::Range,
+    Iter as Object::Iter,
 );
 
 pub fn create_operators() -> Vec<Operator<Rule>> {
     vec![
-        Operator::new(Rule::op_assign, Assoc::Left),
+        // `a = b = c` is `a = (b = c)`
+        Operator::new(Rule::op_assign, Assoc::Right)
+            | Operator::new(Rule::op_plus_assign, Assoc::Right)
+            | Operator::new(Rule::op_minus_assign, Assoc::Right)
+            | Operator::new(Rule::op_times_assign, Assoc::Right)
+            | Operator::new(Rule::op_divide_assign, Assoc::Right)
+            | Operator::new(Rule::op_modulo_assign, Assoc::Right),
         Operator::new(Rule::op_or, Assoc::Left),
         Operator::new(Rule::op_and, Assoc::Left),
         Operator::new(Rule::op_equal, Assoc::Left) | Operator::new(Rule::op_not_equal, Assoc::Left),
@@ -465,9 +778,20 @@ pub fn create_operators() -> Vec<Operator<Rule>> {
             | Operator::new(Rule::op_greater_equal, Assoc::Left)
             | Operator::new(Rule::op_lower, Assoc::Left)
             | Operator::new(Rule::op_lower_equal, Assoc::Left),
+        // `0..10 step 2` is `(0..10) step 2`
+        Operator::new(Rule::op_step, Assoc::Left),
+        Operator::new(Rule::op_range, Assoc::Left) | Operator::new(Rule::op_range_inclusive, Assoc::Left),
+        Operator::new(Rule::op_bit_or, Assoc::Left),
+        Operator::new(Rule::op_bit_xor, Assoc::Left),
+        Operator::new(Rule::op_bit_and, Assoc::Left),
+        Operator::new(Rule::op_shift_left, Assoc::Left) | Operator::new(Rule::op_shift_right, Assoc::Left),
         Operator::new(Rule::op_plus, Assoc::Left) | Operator::new(Rule::op_minus, Assoc::Left),
-        Operator::new(Rule::op_times, Assoc::Left) | Operator::new(Rule::op_divide, Assoc::Left),
-        Operator::new(Rule::op_dot, Assoc::Left),
+        Operator::new(Rule::op_times, Assoc::Left)
+            | Operator::new(Rule::op_divide, Assoc::Left)
+            | Operator::new(Rule::op_floor_divide, Assoc::Left)
+            | Operator::new(Rule::op_modulo, Assoc::Left),
+        // `2 ** 3 ** 2` is `2 ** 9`
+        Operator::new(Rule::op_power, Assoc::Right),
         // Operator::new(Rule::op_unary_not, Assoc::Left)
     ]
 }
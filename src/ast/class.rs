@@ -0,0 +1,127 @@
+use crate::ast::{Func, Ident, Object};
+use crate::error::Error;
+use crate::interpreter::{Exec, Interpreter};
+
+use super::function::UserFn;
+
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::ptr;
+use std::rc::Rc;
+
+pub type Class = Rc<LoxClass>;
+pub type Instance = Rc<RefCell<LoxInstance>>;
+
+pub struct LoxClass {
+    pub name: Ident,
+    pub superclass: Option<Class>,
+    pub methods: HashMap<Ident, Func>,
+}
+
+impl LoxClass {
+    pub fn new(name: Ident, superclass: Option<Class>, methods: HashMap<Ident, Func>) -> Self {
+        Self {
+            name,
+            superclass,
+            methods,
+        }
+    }
+
+    /// Find a method on this class, falling back to the superclass chain.
+    pub fn find_method(&self, name: &Ident) -> Option<Func> {
+        self.methods.get(name).cloned().or_else(|| {
+            self.superclass
+                .as_ref()
+                .and_then(|superclass| superclass.find_method(name))
+        })
+    }
+
+    /// The number of arguments the class' initializer takes.
+    pub fn arity(&self) -> usize {
+        self.find_method(&Ident("init".into()))
+            .map(|init| init.borrow().arity())
+            .unwrap_or(0)
+    }
+
+    /// Create a new instance of `class`, running its `init` method if it has one.
+    pub fn instantiate(
+        class: &Class,
+        interpreter: &mut Interpreter,
+        args: &[Object],
+    ) -> Result<Exec, Error> {
+        let instance: Instance = Rc::new(RefCell::new(LoxInstance::new(class.clone())));
+
+        if let Some(init) = class.find_method(&Ident("init".into())) {
+            let bound = bind(&init, instance.clone());
+            bound.borrow().call(interpreter, args)?;
+        } else if !args.is_empty() {
+            return Err(Error::ArgumentArity(0, args.len()));
+        }
+
+        Ok(Exec::Value(Object::Instance(instance)))
+    }
+}
+
+impl PartialEq for LoxClass {
+    fn eq(&self, other: &Self) -> bool {
+        ptr::eq(self, other)
+    }
+}
+
+impl PartialOrd for LoxClass {
+    fn partial_cmp(&self, _: &Self) -> Option<Ordering> {
+        None
+    }
+}
+
+pub struct LoxInstance {
+    pub class: Class,
+    pub fields: HashMap<Ident, Object>,
+}
+
+impl LoxInstance {
+    pub fn new(class: Class) -> Self {
+        Self {
+            class,
+            fields: HashMap::new(),
+        }
+    }
+}
+
+impl PartialEq for LoxInstance {
+    fn eq(&self, other: &Self) -> bool {
+        ptr::eq(self, other)
+    }
+}
+
+impl PartialOrd for LoxInstance {
+    fn partial_cmp(&self, _: &Self) -> Option<Ordering> {
+        None
+    }
+}
+
+/// Look up `name` on `instance`. Fields shadow methods, and methods are bound to
+/// the instance so `this` resolves inside of them.
+pub fn get_property(instance: &Instance, name: &Ident) -> Option<Object> {
+    if let Some(field) = instance.borrow().fields.get(name) {
+        return Some(field.clone());
+    }
+
+    let method = instance.borrow().class.find_method(name)?;
+    Some(Object::Func(bind(&method, instance.clone())))
+}
+
+pub fn set_property(instance: &Instance, name: Ident, value: Object) {
+    instance.borrow_mut().fields.insert(name, value);
+}
+
+/// Create a copy of `method` whose closure has `this` defined as `instance`.
+pub fn bind(method: &Func, instance: Instance) -> Func {
+    let method = method.borrow();
+    let user = method
+        .downcast_ref::<UserFn>()
+        .expect("class methods are always user functions");
+
+    Rc::new(RefCell::new(Box::new(user.bind(instance))))
+}
@@ -1,11 +1,15 @@
+use crate::ast::class::Instance;
+use crate::ast::generator;
 use crate::ast::visit::Visitor;
 use crate::ast::{Block, Ident, Object};
 use crate::error::Error;
 use crate::interpreter::{Interpreter, Exec};
-use crate::env::Closure;
+use crate::env::{Closure, Scope};
+use crate::parser::Rule;
 use downcast_rs::{Downcast, impl_downcast};
+use pest::iterators::Pair;
 
-use std::cell::Cell;
+use std::cell::RefCell;
 use std::fmt;
 use std::rc::Rc;
 
@@ -19,6 +23,18 @@ pub trait LoxFn: Downcast {
 }
 impl_downcast!(LoxFn);
 
+/// The Rust side of a native function or method.
+pub type Body = fn(&mut Interpreter, &[Object]) -> Result<Exec, Error>;
+
+/// A native method `name(args...)`, bound to `receiver` so `body` can find it
+/// as `this`.
+pub fn bind_native(receiver: Object, name: &Ident, args: &[&str], body: Body) -> Object {
+    let args: Vec<Ident> = args.iter().map(|a| Ident(a.to_string())).collect();
+    let method = BuiltinFn::new(args.len(), args, name.clone(), Vec::new(), body);
+
+    Object::Func(Rc::new(RefCell::new(Box::new(method.bind(receiver)))))
+}
+
 pub struct BuiltinFn {
     pub arity: usize,
     pub args: Vec<Ident>,
@@ -28,7 +44,7 @@ pub struct BuiltinFn {
 }
 
 impl BuiltinFn {
-    pub fn new<F>(
+    pub fn new(
         arity: usize,
         args: Vec<Ident>,
         name: Ident,
@@ -43,6 +59,17 @@ impl BuiltinFn {
             body,
         }
     }
+
+    /// A copy of this function that finds `receiver` as `this`.
+    pub fn bind(&self, receiver: Object) -> Self {
+        let scope: Scope = Default::default();
+        scope.borrow_mut().insert(Ident("this".into()), receiver);
+
+        let mut closure = self.closure.clone();
+        closure.push(scope);
+
+        BuiltinFn::new(self.arity, self.args.clone(), self.name.clone(), closure, self.body)
+    }
 }
 
 impl LoxFn for BuiltinFn {
@@ -63,7 +90,15 @@ impl LoxFn for BuiltinFn {
     // }
 
     fn call(&self, interpreter: &mut Interpreter, args: &[Object]) -> Result<Exec, Error> {
-        (self.body)(interpreter, args)
+        if self.closure.is_empty() {
+            return (self.body)(interpreter, args);
+        }
+
+        let caller = interpreter.push_closure(self.closure.clone());
+        let res = (self.body)(interpreter, args);
+        interpreter.pop_closure(caller);
+
+        res
     }
 }
 
@@ -71,8 +106,10 @@ pub struct UserFn {
     pub arity: usize,
     pub args: Vec<Ident>,
     pub name: Ident,
-    pub closure: Cell<Closure>,
+    pub closure: RefCell<Closure>,
     pub body: Block,
+    /// Whether the body contains a `yield`, so calls return a generator.
+    pub generator: bool,
 }
 
 impl UserFn {
@@ -81,13 +118,43 @@ impl UserFn {
             arity: args.len(),
             args,
             name,
-            closure: Cell::new(closure),
+            closure: RefCell::new(closure),
+            generator: body.yields(),
             body,
         }
     }
 
-    pub fn set_closure(&self, closure: Closure) {
-        self.closure.set(closure);
+    /// Build a function from a `function` rule: a name, optional parameters, and a body.
+    pub fn from_pair(pair: &Pair<Rule>) -> Self {
+        let pairs: Vec<Pair<Rule>> = pair.clone().into_inner().collect();
+
+        let func_name: Ident = Ident::from_pair(&pairs[0]);
+        let parameters: Vec<Ident> = if pairs.len() == 3 {
+            pairs[1].clone().into_inner().map(|p| Ident::from_pair(&p)).collect()
+        } else {
+            vec![]
+        };
+
+        let body = Block::from_pair(pairs.last().unwrap());
+
+        UserFn::new(func_name, parameters, Default::default(), body)
+    }
+
+    /// A copy of this function that closes over `closure`. Each evaluation of a
+    /// declaration makes its own copy, so closures from different calls don't mix.
+    pub fn with_closure(&self, closure: Closure) -> Self {
+        UserFn::new(self.name.clone(), self.args.clone(), closure, self.body.clone())
+    }
+
+    /// A copy of this function with `this` defined in its closure.
+    pub fn bind(&self, instance: Instance) -> Self {
+        let mut bound = self.closure.borrow().clone();
+
+        let scope: Scope = Default::default();
+        scope.borrow_mut().insert(Ident("this".into()), Object::Instance(instance));
+        bound.push(scope);
+
+        self.with_closure(bound)
     }
 }
 
@@ -111,8 +178,21 @@ impl LoxFn for UserFn {
             return Err(Error::ArgumentArity(self.arity(), args.len()));
         }
 
-        let closure = self.closure.take();
-        interpreter.push_closure(closure);
+        if self.generator {
+            let scope: Scope = Default::default();
+            for (arg_name, arg) in self.args.iter().zip(args) {
+                scope.borrow_mut().insert(arg_name.clone(), arg.clone());
+            }
+
+            let mut closure = self.closure.borrow().clone();
+            closure.push(scope);
+
+            return generator::new(closure, self.body.clone()).map(Exec::Value);
+        }
+
+        let closure = self.closure.borrow().clone();
+        let caller = interpreter.push_closure(closure);
+        interpreter.push_scope();
 
         for (i, arg_name) in self.args.iter().enumerate() {
             interpreter.define(arg_name.clone(), args[i].clone());
@@ -120,8 +200,7 @@ impl LoxFn for UserFn {
 
         let res = interpreter.visit_block(&mut self.body.clone());
 
-        let new_closure = interpreter.pop_scope();
-        self.closure.set(new_closure);
+        interpreter.pop_closure(caller);
 
         res
     }
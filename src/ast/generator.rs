@@ -0,0 +1,88 @@
+use crate::ast::iter::{Cursor, Iter};
+use crate::ast::{Block, Object};
+use crate::env::Closure;
+use crate::error::Error;
+use crate::interpreter::{Interpreter, STACK_SIZE};
+
+use corosensei::stack::DefaultStack;
+use corosensei::{Coroutine, CoroutineResult, Yielder};
+
+use std::cell::RefCell;
+use std::mem;
+use std::rc::Rc;
+
+/// A paused call of a function containing `yield`. Its body runs on its own
+/// stack, with its own interpreter, until the next `yield` or the end.
+pub struct Generator {
+    coroutine: Coroutine<(), Object, Result<(), Error>>,
+    /// A value yielded by `has_next()` that `next()` hasn't returned yet.
+    peeked: Option<Object>,
+    done: bool,
+}
+
+/// Start a generator that will run `body` in `closure`, which already holds the
+/// call's arguments. Nothing runs until the first value is asked for.
+pub fn new(closure: Closure, mut body: Block) -> Result<Object, Error> {
+    let mut inner = Interpreter::for_generator(closure);
+    // Reserved up front but only committed as it's used
+    let stack = DefaultStack::new(STACK_SIZE)?;
+    let coroutine = Coroutine::with_stack(stack, move |yielder: &Yielder<(), Object>, ()| {
+        inner.run_generator(&mut body, yielder)
+    });
+
+    let generator = Generator {
+        coroutine,
+        peeked: None,
+        done: false,
+    };
+
+    Ok(Object::Iter(Rc::new(RefCell::new(Cursor::Generator(Box::new(generator))))))
+}
+
+impl Generator {
+    /// Run until the next `yield`, unless a value is already waiting or the
+    /// body has finished.
+    fn fill(&mut self) -> Result<(), Error> {
+        if self.peeked.is_some() || self.done {
+            return Ok(());
+        }
+
+        match self.coroutine.resume(()) {
+            CoroutineResult::Yield(value) => self.peeked = Some(value),
+            CoroutineResult::Return(result) => {
+                self.done = true;
+                result?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn has_next(&self) -> bool {
+        self.peeked.is_some()
+    }
+
+    pub fn next(&mut self) -> Option<Object> {
+        self.peeked.take()
+    }
+}
+
+/// Make sure a generator behind `iter` has run far enough to know its next
+/// value. The generator is taken out of `iter` while it runs, so a body that
+/// reaches its own iterator gets an error rather than a double borrow.
+pub fn fill(iter: &Iter) -> Result<(), Error> {
+    match &*iter.borrow() {
+        Cursor::Generator(_) => (),
+        Cursor::Running => return Err(Error::GeneratorRunning),
+        _ => return Ok(()),
+    }
+
+    let mut generator = match mem::replace(&mut *iter.borrow_mut(), Cursor::Running) {
+        Cursor::Generator(generator) => generator,
+        _ => unreachable!(),
+    };
+
+    let result = generator.fill();
+    *iter.borrow_mut() = Cursor::Generator(generator);
+    result
+}
@@ -0,0 +1,171 @@
+use crate::ast::function::{self, Body};
+use crate::ast::generator::{self, Generator};
+use crate::ast::list::List;
+use crate::ast::range::Range;
+use crate::ast::{Ident, Object};
+use crate::error::Error;
+use crate::interpreter::{Exec, Interpreter};
+
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::ptr;
+use std::rc::Rc;
+use std::vec;
+
+/// Iterators are shared, so every copy of one advances together.
+pub type Iter = Rc<RefCell<Cursor>>;
+
+/// Where an iterator is up to in the values it walks over.
+pub enum Cursor {
+    /// A list's items by position, so items pushed while looping are seen.
+    List(List, usize),
+    /// A snapshot of a map's keys, a set's members, or a string's characters.
+    Items(vec::IntoIter<Object>),
+    /// The next number a range will produce.
+    Range(Range, Option<isize>),
+    /// A user object with `has_next()` and `next()` methods.
+    Object(Object),
+    Generator(Box<Generator>),
+    /// A generator whose body is running, and so can't be advanced.
+    Running,
+}
+
+impl PartialEq for Cursor {
+    fn eq(&self, other: &Self) -> bool {
+        ptr::eq(self, other)
+    }
+}
+
+impl PartialOrd for Cursor {
+    fn partial_cmp(&self, _: &Self) -> Option<Ordering> {
+        None
+    }
+}
+
+fn new(cursor: Cursor) -> Iter {
+    Rc::new(RefCell::new(cursor))
+}
+
+/// Start iterating over `object`. Instances are iterable when they have an
+/// `iter()` method returning an iterator, or an object with `has_next()` and
+/// `next()` methods.
+pub fn iterate(interpreter: &mut Interpreter, object: Object) -> Result<Iter, Error> {
+    let cursor = match object {
+        Object::Iter(iter) => return Ok(iter),
+        Object::List(list) => Cursor::List(list, 0),
+        Object::Map(map) => {
+            let keys: Vec<Object> = map.borrow().keys().map(|key| key.object().clone()).collect();
+            Cursor::Items(keys.into_iter())
+        }
+        Object::Set(set) => {
+            let members: Vec<Object> = set.borrow().iter().map(|key| key.object().clone()).collect();
+            Cursor::Items(members.into_iter())
+        }
+        Object::Str(s) => {
+            let chars: Vec<Object> = s.chars().map(|c| Object::Str(c.to_string())).collect();
+            Cursor::Items(chars.into_iter())
+        }
+        Object::Range(range) => {
+            let first = range.first();
+            Cursor::Range(range, first)
+        }
+        Object::Instance(_) => {
+            let method = interpreter
+                .method(&object, &Ident("iter".into()))
+                .map_err(|_| Error::NotIterable(object.type_name()))?;
+
+            match interpreter.call_value(method, &mut [])? {
+                Object::Iter(iter) => return Ok(iter),
+                other => Cursor::Object(other),
+            }
+        }
+        other => return Err(Error::NotIterable(other.type_name())),
+    };
+
+    Ok(new(cursor))
+}
+
+/// Whether `object` can be iterated over without calling back into Lox.
+pub fn is_builtin_iterable(object: &Object) -> bool {
+    matches!(
+        object,
+        Object::List(_) | Object::Map(_) | Object::Set(_) | Object::Str(_) | Object::Range(_)
+    )
+}
+
+pub fn has_next(interpreter: &mut Interpreter, iter: &Iter) -> Result<bool, Error> {
+    generator::fill(iter)?;
+
+    let object = match &*iter.borrow() {
+        Cursor::List(list, i) => return Ok(*i < list.borrow().len()),
+        Cursor::Items(items) => return Ok(!items.as_slice().is_empty()),
+        Cursor::Range(_, next) => return Ok(next.is_some()),
+        Cursor::Generator(generator) => return Ok(generator.has_next()),
+        Cursor::Running => unreachable!("`generator::fill` rejects running generators"),
+        Cursor::Object(object) => object.clone(),
+    };
+
+    // The iterator isn't borrowed here, so `has_next()` is free to use it
+    let method = interpreter.method(&object, &Ident("has_next".into()))?;
+    interpreter.call_value(method, &mut [])?.is_truthy()
+}
+
+pub fn next(interpreter: &mut Interpreter, iter: &Iter) -> Result<Object, Error> {
+    generator::fill(iter)?;
+
+    let object = match &mut *iter.borrow_mut() {
+        Cursor::List(list, i) => {
+            let item = list.borrow().get(*i).cloned();
+            *i += 1;
+            return item.ok_or(Error::IteratorExhausted);
+        }
+        Cursor::Items(items) => return items.next().ok_or(Error::IteratorExhausted),
+        Cursor::Range(range, next) => {
+            let item = next.ok_or(Error::IteratorExhausted)?;
+            *next = range.after(item);
+            return Ok(Object::Int(item));
+        }
+        Cursor::Generator(generator) => return generator.next().ok_or(Error::IteratorExhausted),
+        Cursor::Running => unreachable!("`generator::fill` rejects running generators"),
+        Cursor::Object(object) => object.clone(),
+    };
+
+    let method = interpreter.method(&object, &Ident("next".into()))?;
+    interpreter.call_value(method, &mut [])
+}
+
+/// The method `iter.name`, for `has_next` and `next`.
+pub fn get_method(iter: &Iter, name: &Ident) -> Option<Object> {
+    let body: Body = match name.0.as_str() {
+        "has_next" => has_next_method,
+        "next" => next_method,
+        _ => return None,
+    };
+
+    Some(function::bind_native(Object::Iter(iter.clone()), name, &[], body))
+}
+
+/// The `iter()` method of a list, map, set, string or range.
+pub fn iter_method(object: Object) -> Option<Object> {
+    Some(function::bind_native(object, &Ident("iter".into()), &[], iter))
+}
+
+fn this(interpreter: &Interpreter) -> Result<Iter, Error> {
+    Iter::try_from(interpreter.this()?)
+}
+
+fn has_next_method(interpreter: &mut Interpreter, _: &[Object]) -> Result<Exec, Error> {
+    let iter = this(interpreter)?;
+    Ok(Exec::Value(Object::Bool(has_next(interpreter, &iter)?)))
+}
+
+fn next_method(interpreter: &mut Interpreter, _: &[Object]) -> Result<Exec, Error> {
+    let iter = this(interpreter)?;
+    Ok(Exec::Value(next(interpreter, &iter)?))
+}
+
+fn iter(interpreter: &mut Interpreter, _: &[Object]) -> Result<Exec, Error> {
+    let this = interpreter.this()?;
+    Ok(Exec::Value(Object::Iter(iterate(interpreter, this)?)))
+}
@@ -0,0 +1,310 @@
+use crate::ast::function::{self, Body};
+use crate::ast::operator::BinOp;
+use crate::ast::{Ident, Object};
+use crate::error::Error;
+use crate::interpreter::{self, Exec, Interpreter};
+
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::rc::Rc;
+
+/// Lists are shared: `var b = a;` makes `b` and `a` the same list.
+pub type List = Rc<RefCell<Vec<Object>>>;
+
+pub fn new(items: Vec<Object>) -> Object {
+    Object::List(Rc::new(RefCell::new(items)))
+}
+
+thread_local! {
+    /// The lists and maps being displayed, or pairs of them being compared, by
+    /// calls further up the stack.
+    static VISITING: RefCell<Vec<(usize, usize)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Run `f` unless `pair` is already being visited, in which case a container
+/// holds itself and `f` would never finish.
+pub(crate) fn visit<T>(pair: (usize, usize), f: impl FnOnce() -> T) -> Option<T> {
+    if VISITING.with(|v| v.borrow().contains(&pair)) {
+        return None;
+    }
+
+    VISITING.with(|v| v.borrow_mut().push(pair));
+    let result = f();
+    VISITING.with(|v| v.borrow_mut().pop());
+
+    Some(result)
+}
+
+/// `[1, "a"]`, with strings quoted so they stand out from the punctuation. A
+/// list inside itself is shown as `[...]`.
+pub fn display(list: &List) -> String {
+    visit((Rc::as_ptr(list) as usize, 0), || {
+        let items: Vec<String> = list.borrow().iter().map(repr).collect();
+        format!("[{}]", items.join(", "))
+    })
+    .unwrap_or_else(|| "[...]".to_string())
+}
+
+pub(crate) fn repr(item: &Object) -> String {
+    match item {
+        Object::Str(s) => format!("{:?}", s),
+        other => other.to_string(),
+    }
+}
+
+/// `index` as an `isize`. Big ints are out of range of any list of length `len`.
+fn to_isize(index: &Object, len: usize) -> Result<isize, Error> {
+    match index {
+        Object::Int(i) => Ok(*i),
+        Object::BigInt(i) => Err(Error::IndexOutOfRange(i.to_string(), len)),
+        other => Err(Error::InvalidIndex(other.type_name())),
+    }
+}
+
+/// Resolve `index` against a list of length `len`, counting negative indices
+/// from the end.
+pub fn resolve(index: &Object, len: usize) -> Result<usize, Error> {
+    let i = to_isize(index, len)?;
+    let resolved = if i < 0 { i + len as isize } else { i };
+
+    if resolved < 0 || resolved as usize >= len {
+        return Err(Error::IndexOutOfRange(i.to_string(), len));
+    }
+
+    Ok(resolved as usize)
+}
+
+pub fn get(list: &List, index: &Object) -> Result<Object, Error> {
+    let list = list.borrow();
+    let i = resolve(index, list.len())?;
+    Ok(list[i].clone())
+}
+
+pub fn set(list: &List, index: &Object, value: Object) -> Result<Object, Error> {
+    let mut list = list.borrow_mut();
+    let i = resolve(index, list.len())?;
+    list[i] = value.clone();
+    Ok(value)
+}
+
+/// `list[start:end]` as a new list. Missing bounds mean the start or end of the
+/// list, and bounds past either end are clamped rather than an error, unless
+/// they're too big to be an int.
+pub fn slice(list: &List, start: Option<Object>, end: Option<Object>) -> Result<Object, Error> {
+    let list = list.borrow();
+    let len = list.len() as isize;
+    let bound = |bound: Option<Object>, default: isize| -> Result<usize, Error> {
+        let i = match bound {
+            Some(bound) => to_isize(&bound, list.len())?,
+            None => default,
+        };
+        let i = if i < 0 { i + len } else { i };
+
+        Ok(i.max(0).min(len) as usize)
+    };
+
+    let (start, end) = (bound(start, 0)?, bound(end, len)?);
+    let items = if start < end { list[start..end].to_vec() } else { Vec::new() };
+
+    Ok(new(items))
+}
+
+/// The method `list.name`, if there is one.
+pub fn get_method(list: &List, name: &Ident) -> Option<Object> {
+    let (args, body): (&[&str], Body) = match name.0.as_str() {
+        "len" => (&[], len),
+        "push" => (&["item"], push),
+        "pop" => (&[], pop),
+        "insert" => (&["index", "item"], insert),
+        "contains" => (&["item"], contains),
+        "sort" => (&[], sort),
+        "sort_by" => (&["compare"], sort_by),
+        "map" => (&["f"], map),
+        "filter" => (&["f"], filter),
+        "reduce" => (&["f", "initial"], reduce),
+        _ => return None,
+    };
+
+    Some(function::bind_native(Object::List(list.clone()), name, args, body))
+}
+
+fn this(interpreter: &Interpreter) -> Result<List, Error> {
+    List::try_from(interpreter.this()?)
+}
+
+fn len(interpreter: &mut Interpreter, _: &[Object]) -> Result<Exec, Error> {
+    let len = this(interpreter)?.borrow().len();
+    Ok(Exec::Value(Object::Int(len as isize)))
+}
+
+fn push(interpreter: &mut Interpreter, args: &[Object]) -> Result<Exec, Error> {
+    this(interpreter)?.borrow_mut().push(args[0].clone());
+    Ok(Exec::Value(Object::Nil))
+}
+
+fn pop(interpreter: &mut Interpreter, _: &[Object]) -> Result<Exec, Error> {
+    let popped = this(interpreter)?.borrow_mut().pop();
+    popped.map(Exec::Value).ok_or(Error::PopFromEmpty)
+}
+
+/// Insert before `index`, where `index` may also be the list's length.
+fn insert(interpreter: &mut Interpreter, args: &[Object]) -> Result<Exec, Error> {
+    let list = this(interpreter)?;
+    let len = list.borrow().len();
+    let i = match args[0] {
+        Object::Int(i) if i == len as isize => len,
+        ref index => resolve(index, len)?,
+    };
+
+    list.borrow_mut().insert(i, args[1].clone());
+    Ok(Exec::Value(Object::Nil))
+}
+
+fn contains(interpreter: &mut Interpreter, args: &[Object]) -> Result<Exec, Error> {
+    let list = this(interpreter)?;
+
+    for item in list.borrow().iter() {
+        if interpreter::values_equal(item, &args[0])? {
+            return Ok(Exec::Value(Object::Bool(true)));
+        }
+    }
+
+    Ok(Exec::Value(Object::Bool(false)))
+}
+
+/// Sort in place with `<`.
+fn sort(interpreter: &mut Interpreter, _: &[Object]) -> Result<Exec, Error> {
+    sort_with(interpreter, |_, a, b| {
+        if interpreter::values_less(a, b)? {
+            Ok(Ordering::Less)
+        } else if interpreter::values_less(b, a)? {
+            Ok(Ordering::Greater)
+        } else {
+            Ok(Ordering::Equal)
+        }
+    })
+}
+
+/// Sort in place with `compare(a, b)`, which returns a negative number when `a`
+/// comes first, a positive one when `b` does, and zero when they're equal.
+fn sort_by(interpreter: &mut Interpreter, args: &[Object]) -> Result<Exec, Error> {
+    let compare = args[0].clone();
+
+    sort_with(interpreter, |interpreter, a, b| {
+        let order = interpreter.call_value(compare.clone(), &mut [a.clone(), b.clone()])?;
+
+        match order {
+            Object::Int(i) => Ok(i.cmp(&0)),
+            Object::BigInt(i) => Ok(i.sign().cmp(&num_bigint::Sign::NoSign)),
+            Object::Float(f) => Ok(f.partial_cmp(&0.0).unwrap_or(Ordering::Equal)),
+            other => Err(Error::TypeMismatch(other.to_string(), "number".into())),
+        }
+    })
+}
+
+/// A stable sort with a fallible comparison, stopping at its first error.
+fn sort_with<F>(interpreter: &mut Interpreter, mut compare: F) -> Result<Exec, Error>
+where
+    F: FnMut(&mut Interpreter, &Object, &Object) -> Result<Ordering, Error>,
+{
+    let list = this(interpreter)?;
+
+    // The comparison may call back into Lox, so sort a copy and leave the list
+    // free to be read while sorting
+    let mut items = list.borrow().clone();
+    let mut error = None;
+
+    items.sort_by(|a, b| {
+        if error.is_some() {
+            return Ordering::Equal;
+        }
+
+        compare(interpreter, a, b).unwrap_or_else(|e| {
+            error = Some(e);
+            Ordering::Equal
+        })
+    });
+
+    if let Some(e) = error {
+        return Err(e);
+    }
+
+    *list.borrow_mut() = items;
+    Ok(Exec::Value(Object::Nil))
+}
+
+fn map(interpreter: &mut Interpreter, args: &[Object]) -> Result<Exec, Error> {
+    let items = this(interpreter)?.borrow().clone();
+    let mut mapped = Vec::with_capacity(items.len());
+
+    for item in items {
+        mapped.push(interpreter.call_value(args[0].clone(), &mut [item])?);
+    }
+
+    Ok(Exec::Value(new(mapped)))
+}
+
+fn filter(interpreter: &mut Interpreter, args: &[Object]) -> Result<Exec, Error> {
+    let items = this(interpreter)?.borrow().clone();
+    let mut kept = Vec::new();
+
+    for item in items {
+        if interpreter.call_value(args[0].clone(), &mut [item.clone()])?.is_truthy()? {
+            kept.push(item);
+        }
+    }
+
+    Ok(Exec::Value(new(kept)))
+}
+
+/// Fold the list into one value with `f(accumulator, item)`.
+fn reduce(interpreter: &mut Interpreter, args: &[Object]) -> Result<Exec, Error> {
+    let items = this(interpreter)?.borrow().clone();
+    let mut accumulator = args[1].clone();
+
+    for item in items {
+        accumulator = interpreter.call_value(args[0].clone(), &mut [accumulator, item])?;
+    }
+
+    Ok(Exec::Value(accumulator))
+}
+
+/// `+` concatenates lists, and `==` compares them item by item.
+pub fn binop(lhs: &List, op: BinOp, rhs: &List) -> Result<Object, Error> {
+    let equal = || -> Result<bool, Error> {
+        let (lhs, rhs) = (lhs.borrow(), rhs.borrow());
+
+        if lhs.len() != rhs.len() {
+            return Ok(false);
+        }
+
+        for (l, r) in lhs.iter().zip(rhs.iter()) {
+            if !interpreter::values_equal(l, r)? {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    };
+
+    // Lists that hold themselves are equal unless some other element differs
+    let equal = || -> Result<bool, Error> {
+        if Rc::ptr_eq(lhs, rhs) {
+            return Ok(true);
+        }
+
+        visit((Rc::as_ptr(lhs) as usize, Rc::as_ptr(rhs) as usize), equal).unwrap_or(Ok(true))
+    };
+
+    match op {
+        BinOp::Plus => {
+            let mut items = lhs.borrow().clone();
+            items.extend(rhs.borrow().iter().cloned());
+            Ok(new(items))
+        }
+        BinOp::EqEq => Ok(Object::Bool(equal()?)),
+        BinOp::NotEq => Ok(Object::Bool(!equal()?)),
+        _ => Err(Error::InvalidBinaryOperator(display(lhs), op, display(rhs))),
+    }
+}
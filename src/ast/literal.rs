@@ -0,0 +1,232 @@
+use crate::ast::Object;
+use crate::parser::Rule;
+
+use pest::error::{Error, ErrorVariant};
+use pest::iterators::Pair;
+use pest::Span;
+
+use num_bigint::BigInt;
+
+/// Decode an `int` pair, which may have a `0x`, `0o` or `0b` prefix and `_`s.
+pub fn parse_int(pair: &Pair<Rule>) -> Result<Object, Error<Rule>> {
+    let (radix, prefix) = match pair.as_str().get(..2) {
+        Some("0x") => (16, 2),
+        Some("0o") => (8, 2),
+        Some("0b") => (2, 2),
+        _ => (10, 0),
+    };
+    check_separators(pair, prefix, radix)?;
+
+    let digits = pair.as_str()[prefix..].replace('_', "");
+
+    match isize::from_str_radix(&digits, radix) {
+        Ok(int) => Ok(Object::Int(int)),
+        // Too big for an `isize`, the grammar has already checked the digits
+        Err(_) => Ok(BigInt::parse_bytes(digits.as_bytes(), radix).unwrap().into()),
+    }
+}
+
+/// Decode a `float` pair, rejecting literals too large to represent.
+pub fn parse_float(pair: &Pair<Rule>) -> Result<Object, Error<Rule>> {
+    check_separators(pair, 0, 10)?;
+
+    let float: f64 = pair.as_str().replace('_', "").parse().unwrap();
+
+    if float.is_infinite() {
+        let span = pair.as_span();
+        return Err(error_at(
+            pair,
+            span.start(),
+            span.end(),
+            &format!("Float literal `{}` is too large", pair.as_str()),
+        ));
+    }
+
+    Ok(Object::Float(float))
+}
+
+/// Reject a `_` in a number unless it's between two digits, as in `1_000`. The
+/// digits start after a `prefix` like `0x`.
+fn check_separators(pair: &Pair<Rule>, prefix: usize, radix: u32) -> Result<(), Error<Rule>> {
+    let text = pair.as_str();
+    let start = pair.as_span().start() + prefix;
+    let digits = &text[prefix..];
+
+    for (i, _) in digits.match_indices('_') {
+        let before = digits[..i].chars().last().filter(|c| c.is_digit(radix));
+        let after = digits[i + 1..].chars().next().filter(|c| c.is_digit(radix));
+
+        if before.is_none() || after.is_none() {
+            return Err(error_at(
+                pair,
+                start + i,
+                start + i + 1,
+                &format!("`_` in `{}` must go between two digits", text),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// A piece of a string literal: decoded text, or the `expr` inside a `${...}`.
+pub enum Fragment<'i> {
+    Text(String),
+    Code(Pair<'i, Rule>),
+}
+
+/// Decode a `string` pair into its pieces, processing escapes for the quoted forms.
+pub fn parse_string<'i>(pair: &Pair<'i, Rule>) -> Result<Vec<Fragment<'i>>, Error<Rule>> {
+    let inner = pair.clone().into_inner().next().unwrap();
+    let text = inner.as_str();
+
+    match inner.as_rule() {
+        Rule::quoted_string => fragments(inner, false),
+        Rule::triple_string => fragments(inner, true),
+        Rule::raw_string => {
+            let hashes = text[1..].find('"').unwrap();
+            Ok(vec![Fragment::Text(text[hashes + 2..text.len() - hashes - 1].to_string())])
+        }
+        Rule::heredoc => {
+            let body = trim_close(trim_open(&text[3..text.len() - 3]));
+            Ok(vec![Fragment::Text(body.to_string())])
+        }
+        _ => unreachable!(),
+    }
+}
+
+fn fragments(pair: Pair<Rule>, multiline: bool) -> Result<Vec<Fragment>, Error<Rule>> {
+    let parts: Vec<Pair<Rule>> = pair.into_inner().collect();
+    let last = parts.len().saturating_sub(1);
+    let mut fragments = Vec::with_capacity(parts.len());
+
+    for (i, part) in parts.into_iter().enumerate() {
+        if part.as_rule() == Rule::interpolation {
+            fragments.push(Fragment::Code(part.into_inner().next().unwrap()));
+            continue;
+        }
+
+        let mut text = part.as_str();
+        let mut start = part.as_span().start();
+
+        if multiline && i == 0 {
+            let trimmed = trim_open(text);
+            start += text.len() - trimmed.len();
+            text = trimmed;
+        }
+
+        if multiline && i == last {
+            text = trim_close(text);
+        }
+
+        if !text.is_empty() {
+            fragments.push(Fragment::Text(unescape(&part, start, text)?));
+        }
+    }
+
+    Ok(fragments)
+}
+
+/// Drop the line break right after an opening `"""` or ```` ``` ````.
+fn trim_open(body: &str) -> &str {
+    body.strip_prefix("\r\n")
+        .or_else(|| body.strip_prefix('\n'))
+        .unwrap_or(body)
+}
+
+/// Drop the last line if it only holds the indentation of the closing delimiter.
+fn trim_close(body: &str) -> &str {
+    match body.rfind('\n') {
+        Some(end) if body[end..].trim().is_empty() => body[..end].trim_end_matches('\r'),
+        _ => body,
+    }
+}
+
+/// Process the escapes in `body`, which starts at byte `start` of the input.
+fn unescape(pair: &Pair<Rule>, start: usize, body: &str) -> Result<String, Error<Rule>> {
+    let mut value = String::with_capacity(body.len());
+    let mut chars = body.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+
+        let escaped = match chars.next() {
+            Some((_, 'n')) => '\n',
+            Some((_, 't')) => '\t',
+            Some((_, 'r')) => '\r',
+            Some((_, '0')) => '\0',
+            Some((_, '\\')) => '\\',
+            Some((_, '"')) => '"',
+            Some((_, '\'')) => '\'',
+            Some((_, '$')) => '$',
+            // A backslash at the end of a line joins it with the next one
+            Some((_, '\n')) => {
+                while chars.peek().is_some_and(|&(_, c)| c.is_whitespace()) {
+                    chars.next();
+                }
+
+                continue;
+            }
+            Some((_, 'u')) => {
+                let end = match body[i..].find('}') {
+                    Some(end) if chars.peek().map(|&(_, c)| c) == Some('{') => i + end + 1,
+                    _ => {
+                        return Err(error_at(
+                            pair,
+                            start + i,
+                            start + i + 2,
+                            "Unicode escapes are written as `\\u{...}`",
+                        ))
+                    }
+                };
+
+                let digits = &body[i + 3..end - 1];
+                let code = u32::from_str_radix(digits, 16)
+                    .ok()
+                    .filter(|_| (1..=6).contains(&digits.len()))
+                    .and_then(std::char::from_u32);
+
+                while chars.peek().is_some_and(|&(j, _)| j < end) {
+                    chars.next();
+                }
+
+                match code {
+                    Some(c) => c,
+                    None => {
+                        return Err(error_at(
+                            pair,
+                            start + i,
+                            start + end,
+                            &format!("Invalid unicode escape `{}`", &body[i..end]),
+                        ))
+                    }
+                }
+            }
+            Some((j, other)) => {
+                return Err(error_at(
+                    pair,
+                    start + i,
+                    start + j + other.len_utf8(),
+                    &format!("Unknown escape sequence `\\{}`", other),
+                ))
+            }
+            None => unreachable!("the grammar never ends a string on a backslash"),
+        };
+
+        value.push(escaped);
+    }
+
+    Ok(value)
+}
+
+fn error_at(pair: &Pair<Rule>, start: usize, end: usize, message: &str) -> Error<Rule> {
+    Error::new_from_span(
+        ErrorVariant::CustomError {
+            message: message.to_string(),
+        },
+        Span::new(pair.get_input(), start, end).unwrap(),
+    )
+}
@@ -0,0 +1,184 @@
+use crate::ast::function::{self, Body};
+use crate::ast::operator::BinOp;
+use crate::ast::{list, Ident, Object};
+use crate::error::Error;
+use crate::interpreter::{self, Exec, Interpreter};
+
+use derive_more::{Deref, DerefMut};
+use indexmap::IndexMap;
+
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::hash::{Hash, Hasher};
+use std::mem;
+use std::rc::Rc;
+
+/// Maps are shared like lists, and remember the order their keys were added in.
+pub type Map = Rc<RefCell<Entries>>;
+
+/// A map's entries in insertion order.
+#[derive(Debug, Default, Clone, PartialEq, Deref, DerefMut)]
+pub struct Entries(pub IndexMap<Key, Object>);
+
+// Maps have no ordering, but `Object` needs one
+impl PartialOrd for Entries {
+    fn partial_cmp(&self, _: &Self) -> Option<Ordering> {
+        None
+    }
+}
+
+/// A value that can be used as a map key or set member: a string, int, bool or `nil`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Key(Object);
+
+impl Key {
+    pub fn new(key: Object) -> Result<Self, Error> {
+        match key {
+            Object::Str(_) | Object::Int(_) | Object::BigInt(_) | Object::Bool(_) | Object::Nil => {
+                Ok(Key(key))
+            }
+            other => Err(Error::Unhashable(other.type_name())),
+        }
+    }
+
+    pub fn object(&self) -> &Object {
+        &self.0
+    }
+}
+
+impl Eq for Key {}
+
+impl Hash for Key {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        mem::discriminant(&self.0).hash(state);
+
+        match &self.0 {
+            Object::Str(s) => s.hash(state),
+            Object::Int(i) => i.hash(state),
+            Object::BigInt(i) => i.hash(state),
+            Object::Bool(b) => b.hash(state),
+            _ => (),
+        }
+    }
+}
+
+pub fn new(entries: IndexMap<Key, Object>) -> Object {
+    Object::Map(Rc::new(RefCell::new(Entries(entries))))
+}
+
+/// `{"a": 1, 2: true}`, quoting strings like lists do. A map inside itself is
+/// shown as `{...}`.
+pub fn display(map: &Map) -> String {
+    list::visit((Rc::as_ptr(map) as *const () as usize, 0), || {
+        let entries: Vec<String> = map
+            .borrow()
+            .iter()
+            .map(|(key, value)| format!("{}: {}", list::repr(key.object()), list::repr(value)))
+            .collect();
+
+        format!("{{{}}}", entries.join(", "))
+    })
+    .unwrap_or_else(|| "{...}".to_string())
+}
+
+pub fn get(map: &Map, key: Object) -> Result<Object, Error> {
+    let key = Key::new(key)?;
+    let value = map.borrow().get(&key).cloned();
+
+    value.ok_or_else(|| Error::MissingKey(list::repr(key.object())))
+}
+
+pub fn set(map: &Map, key: Key, value: Object) -> Result<Object, Error> {
+    map.borrow_mut().insert(key, value.clone());
+    Ok(value)
+}
+
+/// The method `map.name`, if there is one.
+pub fn get_method(map: &Map, name: &Ident) -> Option<Object> {
+    let (args, body): (&[&str], Body) = match name.0.as_str() {
+        "len" => (&[], len),
+        "has" => (&["key"], has),
+        "get" => (&["key", "default"], get_or),
+        "remove" => (&["key"], remove),
+        "keys" => (&[], keys),
+        "values" => (&[], values),
+        _ => return None,
+    };
+
+    Some(function::bind_native(Object::Map(map.clone()), name, args, body))
+}
+
+fn this(interpreter: &Interpreter) -> Result<Map, Error> {
+    Map::try_from(interpreter.this()?)
+}
+
+fn len(interpreter: &mut Interpreter, _: &[Object]) -> Result<Exec, Error> {
+    let len = this(interpreter)?.borrow().len();
+    Ok(Exec::Value(Object::Int(len as isize)))
+}
+
+fn has(interpreter: &mut Interpreter, args: &[Object]) -> Result<Exec, Error> {
+    let key = Key::new(args[0].clone())?;
+    let found = this(interpreter)?.borrow().contains_key(&key);
+    Ok(Exec::Value(Object::Bool(found)))
+}
+
+/// `map[key]`, or `default` when the key is missing.
+fn get_or(interpreter: &mut Interpreter, args: &[Object]) -> Result<Exec, Error> {
+    let key = Key::new(args[0].clone())?;
+    let value = this(interpreter)?.borrow().get(&key).cloned();
+    Ok(Exec::Value(value.unwrap_or_else(|| args[1].clone())))
+}
+
+/// Remove `key`, returning its value, or `nil` if it wasn't there.
+fn remove(interpreter: &mut Interpreter, args: &[Object]) -> Result<Exec, Error> {
+    let key = Key::new(args[0].clone())?;
+    let removed = this(interpreter)?.borrow_mut().shift_remove(&key);
+    Ok(Exec::Value(removed.unwrap_or(Object::Nil)))
+}
+
+fn keys(interpreter: &mut Interpreter, _: &[Object]) -> Result<Exec, Error> {
+    let keys = this(interpreter)?.borrow().keys().map(|key| key.object().clone()).collect();
+    Ok(Exec::Value(list::new(keys)))
+}
+
+fn values(interpreter: &mut Interpreter, _: &[Object]) -> Result<Exec, Error> {
+    let values = this(interpreter)?.borrow().values().cloned().collect();
+    Ok(Exec::Value(list::new(values)))
+}
+
+/// `==` compares maps entry by entry, ignoring their order.
+pub fn binop(lhs: &Map, op: BinOp, rhs: &Map) -> Result<Object, Error> {
+    let equal = || -> Result<bool, Error> {
+        let (lhs, rhs) = (lhs.borrow(), rhs.borrow());
+
+        if lhs.len() != rhs.len() {
+            return Ok(false);
+        }
+
+        for (key, l) in lhs.iter() {
+            match rhs.get(key) {
+                Some(r) if interpreter::values_equal(l, r)? => (),
+                _ => return Ok(false),
+            }
+        }
+
+        Ok(true)
+    };
+
+    let equal = || -> Result<bool, Error> {
+        if Rc::ptr_eq(lhs, rhs) {
+            return Ok(true);
+        }
+
+        let pair = (Rc::as_ptr(lhs) as *const () as usize, Rc::as_ptr(rhs) as *const () as usize);
+        list::visit(pair, equal).unwrap_or(Ok(true))
+    };
+
+    match op {
+        BinOp::EqEq => Ok(Object::Bool(equal()?)),
+        BinOp::NotEq => Ok(Object::Bool(!equal()?)),
+        _ => Err(Error::InvalidBinaryOperator(display(lhs), op, display(rhs))),
+    }
+}
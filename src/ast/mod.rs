@@ -1,7 +1,15 @@
 pub(crate) mod ast;
+pub(crate) mod class;
 pub(crate) mod function;
+pub(crate) mod generator;
+pub(crate) mod iter;
+pub(crate) mod list;
+pub(crate) mod map;
+pub(crate) mod literal;
 pub(crate) mod operator;
+pub(crate) mod set;
 pub(crate) mod printer;
+pub(crate) mod range;
 pub(crate) mod span;
 pub(crate) mod visit;
 
@@ -2,6 +2,12 @@ use super::Object;
 use crate::error::Error;
 use crate::parser::Rule;
 use derive_more::Display;
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+
+use std::cmp::Ordering;
+use std::convert::TryFrom;
 
 #[derive(Debug, Clone, PartialEq, PartialOrd, Display)]
 pub enum UnOp {
@@ -23,8 +29,23 @@ pub enum BinOp {
     Times,
     #[display(fmt = "/")]
     Divide,
+    #[display(fmt = "//")]
+    FloorDiv,
+    #[display(fmt = "%")]
+    Mod,
+    #[display(fmt = "**")]
+    Pow,
+    #[display(fmt = "&")]
+    BitAnd,
+    #[display(fmt = "|")]
+    BitOr,
+    #[display(fmt = "^")]
+    BitXor,
+    #[display(fmt = "<<")]
+    Shl,
+    #[display(fmt = ">>")]
+    Shr,
     #[display(fmt = ">")]
-    // Mod,
     Gt,
     #[display(fmt = ">=")]
     Ge,
@@ -32,13 +53,17 @@ pub enum BinOp {
     Lt,
     #[display(fmt = "<=")]
     Le,
-    #[display(fmt = "!=")]
-    Ne,
     #[display(fmt = "==")]
     EqEq,
     #[display(fmt = "!=")]
     NotEq,
     // Not,
+    #[display(fmt = "..")]
+    Range,
+    #[display(fmt = "..=")]
+    RangeInclusive,
+    #[display(fmt = "step")]
+    Step,
     #[display(fmt = "and")]
     And,
     #[display(fmt = "or")]
@@ -47,6 +72,14 @@ pub enum BinOp {
     Dot,
 }
 
+/// Whether `++`/`--` comes before its target, evaluating to the new value, or
+/// after it, evaluating to the old one.
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub enum Fixity {
+    Prefix,
+    Postfix,
+}
+
 impl From<Rule> for BinOp {
     fn from(rule: Rule) -> Self {
         match rule {
@@ -54,12 +87,30 @@ impl From<Rule> for BinOp {
             Rule::op_minus => BinOp::Minus,
             Rule::op_times => BinOp::Times,
             Rule::op_divide => BinOp::Divide,
+            Rule::op_floor_divide => BinOp::FloorDiv,
+            Rule::op_modulo => BinOp::Mod,
+            Rule::op_power => BinOp::Pow,
+            Rule::op_bit_and => BinOp::BitAnd,
+            Rule::op_bit_or => BinOp::BitOr,
+            Rule::op_bit_xor => BinOp::BitXor,
+            Rule::op_shift_left => BinOp::Shl,
+            Rule::op_shift_right => BinOp::Shr,
+            Rule::op_plus_assign => BinOp::Plus,
+            Rule::op_minus_assign => BinOp::Minus,
+            Rule::op_times_assign => BinOp::Times,
+            Rule::op_divide_assign => BinOp::Divide,
+            Rule::op_modulo_assign => BinOp::Mod,
+            Rule::op_increment => BinOp::Plus,
+            Rule::op_decrement => BinOp::Minus,
             Rule::op_equal => BinOp::EqEq,
             Rule::op_not_equal => BinOp::NotEq,
             Rule::op_greater => BinOp::Gt,
             Rule::op_greater_equal => BinOp::Ge,
             Rule::op_lower => BinOp::Lt,
             Rule::op_lower_equal => BinOp::Le,
+            Rule::op_range => BinOp::Range,
+            Rule::op_range_inclusive => BinOp::RangeInclusive,
+            Rule::op_step => BinOp::Step,
             Rule::op_and => BinOp::And,
             Rule::op_or => BinOp::Or,
             // Rule::op_plus => Op::Plus,
@@ -76,10 +127,21 @@ pub fn is_binop(rule: Rule) -> bool {
         | Rule::op_times
         | Rule::op_greater_equal
         | Rule::op_divide
+        | Rule::op_floor_divide
+        | Rule::op_modulo
+        | Rule::op_power
+        | Rule::op_bit_and
+        | Rule::op_bit_or
+        | Rule::op_bit_xor
+        | Rule::op_shift_left
+        | Rule::op_shift_right
         | Rule::op_lower
         | Rule::op_equal
         | Rule::op_lower_equal
         | Rule::op_not_equal
+        | Rule::op_range
+        | Rule::op_range_inclusive
+        | Rule::op_step
         | Rule::op_and
         | Rule::op_or => true,
         // Rule::op_plus => Op::Plus,
@@ -87,9 +149,21 @@ pub fn is_binop(rule: Rule) -> bool {
     }
 }
 
+/// `+=` and friends, which apply their operator and assign the result.
+pub fn is_compound_assign(rule: Rule) -> bool {
+    matches!(
+        rule,
+        Rule::op_plus_assign
+            | Rule::op_minus_assign
+            | Rule::op_times_assign
+            | Rule::op_divide_assign
+            | Rule::op_modulo_assign
+    )
+}
+
 pub fn is_unop(rule: Rule) -> bool {
     match rule {
-        Rule::op_minus | Rule::op_unary_not => true,
+        Rule::op_minus | Rule::op_unary_not | Rule::op_unary_tilde => true,
         _ => false,
     }
 }
@@ -107,18 +181,154 @@ pub trait BinaryOp {
     }
 }
 
+/// The result of a checked int operation, redone with big integers if it overflowed.
+fn checked(result: Option<isize>, lhs: &isize, op: BinOp, rhs: &isize) -> Result<Object, Error> {
+    match result {
+        Some(value) => Ok(value.into()),
+        None => BigInt::from(*lhs).binop(op, &BigInt::from(*rhs)),
+    }
+}
+
 impl BinaryOp for isize {
+    fn binop(&self, op: BinOp, rhs: &Self) -> Result<Object, Error> {
+        Ok(match op {
+            BinOp::Plus => checked(self.checked_add(*rhs), self, op, rhs)?,
+            BinOp::Minus => checked(self.checked_sub(*rhs), self, op, rhs)?,
+            BinOp::Times => checked(self.checked_mul(*rhs), self, op, rhs)?,
+            BinOp::Divide if *rhs == 0 => Err(Error::DivisionByZero)?,
+            // `/` is true division, `1 / 2` is `0.5`
+            BinOp::Divide => (*self as f64 / *rhs as f64).into(),
+            BinOp::FloorDiv | BinOp::Mod if *rhs == 0 => Err(Error::DivisionByZero)?,
+            // Both round toward negative infinity: `-7 // 2` is `-4` and `-7 % 2` is `1`
+            BinOp::FloorDiv => checked(self.checked_div(*rhs).map(|_| Integer::div_floor(self, rhs)), self, op, rhs)?,
+            BinOp::Mod => checked(self.checked_rem(*rhs).map(|_| Integer::mod_floor(self, rhs)), self, op, rhs)?,
+            BinOp::Pow if *rhs < 0 => (*self as f64).powf(*rhs as f64).into(),
+            BinOp::Pow => checked(u32::try_from(*rhs).ok().and_then(|e| self.checked_pow(e)), self, op, rhs)?,
+            BinOp::BitAnd => (self & rhs).into(),
+            BinOp::BitOr => (self | rhs).into(),
+            BinOp::BitXor => (self ^ rhs).into(),
+            BinOp::Shl | BinOp::Shr if *rhs < 0 => Err(Error::NegativeShift(rhs.to_string()))?,
+            BinOp::Shl => {
+                let shifted = u32::try_from(*rhs)
+                    .ok()
+                    .and_then(|bits| self.checked_shl(bits))
+                    .filter(|shifted| shifted >> rhs == *self);
+
+                checked(shifted, self, op, rhs)?
+            }
+            BinOp::Shr => (self >> (*rhs).min(isize::BITS as isize - 1)).into(),
+            BinOp::Gt => (self > rhs).into(),
+            BinOp::Ge => (self >= rhs).into(),
+            BinOp::Lt => (self < rhs).into(),
+            BinOp::Le => (self <= rhs).into(),
+            BinOp::EqEq => (self == rhs).into(),
+            BinOp::NotEq => (self != rhs).into(),
+            _ => Err(Error::InvalidBinaryOperator(
+                self.to_string(),
+                op,
+                rhs.to_string(),
+            ))?,
+        })
+    }
+}
+
+/// The most bits a `<<` or `**` result may have. Anything larger would take a
+/// huge allocation, and likely abort.
+const MAX_BITS: u64 = 1 << 20;
+
+/// `num / den` as the nearest float, even when both are too large to be floats.
+pub fn divide(num: &BigInt, den: &BigInt) -> f64 {
+    // Scale the numerator so the quotient keeps at least 64 significant bits
+    let shift = (den.bits() as i64 - num.bits() as i64 + 64).max(0);
+    let quotient = (num << shift as usize) / den;
+
+    // Undo the scaling in two steps, so that neither underflows on its own
+    let half = (shift / 2) as i32;
+    quotient.to_f64().unwrap() * 2f64.powi(-half) * 2f64.powi(half - shift as i32)
+}
+
+/// A finite float as an exact fraction `(num, den)`, where `den` is a positive
+/// power of two.
+fn fraction(float: f64) -> (BigInt, BigInt) {
+    let bits = float.to_bits();
+    let exponent = ((bits >> 52) & 0x7ff) as i64;
+    let mantissa = match bits & 0xf_ffff_ffff_ffff {
+        fraction if exponent == 0 => fraction << 1,
+        fraction => fraction | 1 << 52,
+    };
+
+    let mut num = BigInt::from(mantissa) << (exponent - 1075).max(0) as usize;
+    if float.is_sign_negative() {
+        num = -num;
+    }
+
+    (num, BigInt::one() << (1075 - exponent).max(0) as usize)
+}
+
+/// A big int combined with a float, worked out exactly and only then rounded,
+/// since the big int may be too large to be a float itself. `None` for the
+/// operators that just promote both to floats.
+pub fn big_float_binop(lhs: &Object, op: BinOp, rhs: &Object) -> Option<Result<Object, Error>> {
+    let ((ln, ld), (rn, rd)) = match (lhs, rhs) {
+        (Object::BigInt(l), Object::Float(r)) if r.is_finite() => ((l.clone(), BigInt::one()), fraction(*r)),
+        (Object::Float(l), Object::BigInt(r)) if l.is_finite() => (fraction(*l), (r.clone(), BigInt::one())),
+        _ => return None,
+    };
+
+    // Denominators are positive, so comparing cross products compares the values
+    let ordering = (&ln * &rd).cmp(&(&rn * &ld));
+
+    Some(Ok(match op {
+        BinOp::Plus => divide(&(&ln * &rd + &rn * &ld), &(&ld * &rd)).into(),
+        BinOp::Minus => divide(&(&ln * &rd - &rn * &ld), &(&ld * &rd)).into(),
+        BinOp::Times => divide(&(&ln * &rn), &(&ld * &rd)).into(),
+        BinOp::Divide if rn.is_zero() => return Some(Err(Error::DivisionByZero)),
+        BinOp::Divide => divide(&(&ln * &rd), &(&ld * &rn)).into(),
+        BinOp::Gt => (ordering == Ordering::Greater).into(),
+        BinOp::Ge => (ordering != Ordering::Less).into(),
+        BinOp::Lt => (ordering == Ordering::Less).into(),
+        BinOp::Le => (ordering != Ordering::Greater).into(),
+        BinOp::EqEq => (ordering == Ordering::Equal).into(),
+        BinOp::NotEq => (ordering != Ordering::Equal).into(),
+        _ => return None,
+    }))
+}
+
+impl BinaryOp for BigInt {
     fn binop(&self, op: BinOp, rhs: &Self) -> Result<Object, Error> {
         Ok(match op {
             BinOp::Plus => (self + rhs).into(),
             BinOp::Minus => (self - rhs).into(),
             BinOp::Times => (self * rhs).into(),
-            BinOp::Divide => (self / rhs).into(),
+            BinOp::Divide if rhs.is_zero() => Err(Error::DivisionByZero)?,
+            BinOp::Divide => divide(self, rhs).into(),
+            BinOp::FloorDiv | BinOp::Mod if rhs.is_zero() => Err(Error::DivisionByZero)?,
+            BinOp::FloorDiv => Integer::div_floor(self, rhs).into(),
+            BinOp::Mod => Integer::mod_floor(self, rhs).into(),
+            BinOp::Pow if rhs.is_negative() => self.to_f64().unwrap().powf(rhs.to_f64().unwrap()).into(),
+            // Powers of 0, 1 and -1 never grow, anything else gains a bit per step
+            BinOp::Pow if self.abs() <= BigInt::from(1) => {
+                let exponent = if rhs.is_zero() { 0 } else if rhs.is_even() { 2 } else { 1 };
+                self.pow(exponent).into()
+            }
+            BinOp::Pow => match rhs.to_u32() {
+                Some(exponent) if (self.bits() - 1) * u64::from(exponent) <= MAX_BITS => self.pow(exponent).into(),
+                _ => Err(Error::OperandTooLarge(rhs.to_string(), op))?,
+            },
+            BinOp::BitAnd => (self & rhs).into(),
+            BinOp::BitOr => (self | rhs).into(),
+            BinOp::BitXor => (self ^ rhs).into(),
+            BinOp::Shl | BinOp::Shr if rhs.is_negative() => Err(Error::NegativeShift(rhs.to_string()))?,
+            BinOp::Shl if self.is_zero() => BigInt::zero().into(),
+            BinOp::Shl => match rhs.to_u64() {
+                Some(bits) if self.bits() + bits <= MAX_BITS => (self << bits).into(),
+                _ => Err(Error::OperandTooLarge(rhs.to_string(), op))?,
+            },
+            BinOp::Shr => (self >> rhs.to_usize().unwrap_or(usize::MAX)).into(),
             BinOp::Gt => (self > rhs).into(),
             BinOp::Ge => (self >= rhs).into(),
             BinOp::Lt => (self < rhs).into(),
             BinOp::Le => (self <= rhs).into(),
-            BinOp::Ne => (self != rhs).into(),
             BinOp::EqEq => (self == rhs).into(),
             BinOp::NotEq => (self != rhs).into(),
             _ => Err(Error::InvalidBinaryOperator(
@@ -130,18 +340,22 @@ impl BinaryOp for isize {
     }
 }
 
-impl BinaryOp for f32 {
+impl BinaryOp for f64 {
     fn binop(&self, op: BinOp, rhs: &Self) -> Result<Object, Error> {
         Ok(match op {
             BinOp::Plus => (self + rhs).into(),
             BinOp::Minus => (self - rhs).into(),
             BinOp::Times => (self * rhs).into(),
+            BinOp::Divide if *rhs == 0.0 => Err(Error::DivisionByZero)?,
             BinOp::Divide => (self / rhs).into(),
+            BinOp::FloorDiv | BinOp::Mod if *rhs == 0.0 => Err(Error::DivisionByZero)?,
+            BinOp::FloorDiv => (self / rhs).floor().into(),
+            BinOp::Mod => (self - rhs * (self / rhs).floor()).into(),
+            BinOp::Pow => self.powf(*rhs).into(),
             BinOp::Gt => (self > rhs).into(),
             BinOp::Ge => (self >= rhs).into(),
             BinOp::Lt => (self < rhs).into(),
             BinOp::Le => (self <= rhs).into(),
-            BinOp::Ne => (self != rhs).into(),
             BinOp::EqEq => (self == rhs).into(),
             BinOp::NotEq => (self != rhs).into(),
             _ => Err(Error::InvalidBinaryOperator(
@@ -160,11 +374,13 @@ impl BinaryOp for bool {
             BinOp::Ge => (self >= rhs).into(),
             BinOp::Lt => (self < rhs).into(),
             BinOp::Le => (self <= rhs).into(),
-            BinOp::Ne => (self != rhs).into(),
             BinOp::EqEq => (self == rhs).into(),
             BinOp::NotEq => (self != rhs).into(),
             BinOp::And => (*self && *rhs).into(),
             BinOp::Or => (*self || *rhs).into(),
+            BinOp::BitAnd => (self & rhs).into(),
+            BinOp::BitOr => (self | rhs).into(),
+            BinOp::BitXor => (self ^ rhs).into(),
             _ => Err(Error::InvalidBinaryOperator(
                 self.to_string(),
                 op,
@@ -174,6 +390,25 @@ impl BinaryOp for bool {
     }
 }
 
+impl BinaryOp for String {
+    fn binop(&self, op: BinOp, rhs: &Self) -> Result<Object, Error> {
+        Ok(match op {
+            BinOp::Plus => (self.clone() + rhs).into(),
+            BinOp::Gt => (self > rhs).into(),
+            BinOp::Ge => (self >= rhs).into(),
+            BinOp::Lt => (self < rhs).into(),
+            BinOp::Le => (self <= rhs).into(),
+            BinOp::EqEq => (self == rhs).into(),
+            BinOp::NotEq => (self != rhs).into(),
+            _ => Err(Error::InvalidBinaryOperator(
+                format!("{:?}", self),
+                op,
+                format!("{:?}", rhs),
+            ))?,
+        })
+    }
+}
+
 pub trait UnaryOp {
     fn unop(&self, op: UnOp) -> Result<Object, Error>
     where
@@ -186,13 +421,27 @@ pub trait UnaryOp {
 impl UnaryOp for isize {
     fn unop(&self, op: UnOp) -> Result<Object, Error> {
         Ok(match op {
-            UnOp::Not | UnOp::Tilde => (!self).into(),
-            UnOp::Minus => (-1 * self).into(),
+            UnOp::Tilde => (!self).into(),
+            UnOp::Minus => match self.checked_neg() {
+                Some(negated) => negated.into(),
+                None => (-BigInt::from(*self)).into(),
+            },
+            UnOp::Not => Err(Error::InvalidUnaryOperator(op, self.to_string()))?,
+        })
+    }
+}
+
+impl UnaryOp for BigInt {
+    fn unop(&self, op: UnOp) -> Result<Object, Error> {
+        Ok(match op {
+            UnOp::Tilde => (!self.clone()).into(),
+            UnOp::Minus => (-self.clone()).into(),
+            UnOp::Not => Err(Error::InvalidUnaryOperator(op, self.to_string()))?,
         })
     }
 }
 
-impl UnaryOp for f32 {
+impl UnaryOp for f64 {
     fn unop(&self, op: UnOp) -> Result<Object, Error> {
         Ok(match op {
             UnOp::Minus => (-1.0 * self).into(),
@@ -27,12 +27,19 @@ impl Visitor for Printer {
                 self.visit_expr(lhs)?;
                 self.visit_expr(rhs)?;
             }
-            Expr::Access(lhs, rhs) => {
-                println!("{}[accs]", " ".repeat(self.0));
+            Expr::CompoundAssign(lhs, op, rhs) => {
+                println!("{}[asgn] {}=", " ".repeat(self.0), op);
                 self.visit_expr(lhs)?;
-                // println!("{}[.]", " ".repeat(self.0));
                 self.visit_expr(rhs)?;
             }
+            Expr::Increment(target, op, fixity) => {
+                println!("{}[incr] {}{} {:?}", " ".repeat(self.0), op, op, fixity);
+                self.visit_expr(target)?;
+            }
+            Expr::Access(lhs, name) => {
+                println!("{}[accs] .{}", " ".repeat(self.0), name);
+                self.visit_expr(lhs)?;
+            }
             Expr::UnOp(op, rhs) => {
                 println!("{}[unop] {}", " ".repeat(self.0), op);
                 self.visit_expr(rhs)?;
@@ -42,13 +49,9 @@ impl Visitor for Printer {
                 self.visit_expr(lhs)?;
                 self.visit_expr(rhs)?;
             }
-            Expr::Call(ident, args) => {
-                print!("{}[call] {} (", " ".repeat(self.0), ident);
-                if args.is_empty() {
-                    println!(")");
-                } else {
-                    println!();
-                }
+            Expr::Call(callee, args) => {
+                println!("{}[call] ({})", " ".repeat(self.0), args.len());
+                self.visit_expr(callee)?;
 
                 for arg in args {
                     self.visit_expr(arg)?;
@@ -57,6 +60,46 @@ impl Visitor for Printer {
             Expr::Object(o) => {
                 self.visit_obj(o)?;
             }
+            Expr::List(items) => {
+                println!("{}[list] ({})", " ".repeat(self.0), items.len());
+
+                for item in items {
+                    self.visit_expr(item)?;
+                }
+            }
+            Expr::Map(entries) => {
+                println!("{}[map ] ({})", " ".repeat(self.0), entries.len());
+
+                for (key, value) in entries {
+                    self.visit_expr(key)?;
+                    self.visit_expr(value)?;
+                }
+            }
+            Expr::Index(list, index) => {
+                println!("{}[indx]", " ".repeat(self.0));
+                self.visit_expr(list)?;
+                self.visit_expr(index)?;
+            }
+            Expr::Slice(list, start, end) => {
+                println!("{}[slce]", " ".repeat(self.0));
+                self.visit_expr(list)?;
+
+                for bound in start.iter_mut().chain(end.iter_mut()) {
+                    self.visit_expr(bound)?;
+                }
+            }
+            Expr::Interpolation(parts) => {
+                println!("{}[intp] ({})", " ".repeat(self.0), parts.len());
+
+                for part in parts {
+                    self.visit_expr(part)?;
+                }
+            }
+            Expr::Lambda(params, body) => {
+                let params: Vec<String> = params.iter().map(ToString::to_string).collect();
+                println!("{}[lmbd] ({})", " ".repeat(self.0), params.join(", "));
+                self.visit_block(body)?;
+            }
         }
 
         self.0 -= 2;
@@ -97,6 +140,10 @@ impl Visitor for Printer {
                 println!("print");
                 self.visit_expr(e)?;
             }
+            Stmt::Yield(e) => {
+                println!("yield");
+                self.visit_expr(e)?;
+            }
             Stmt::VarDecl(ident, init) => {
                 println!("var");
                 println!("{}[idnt]: {}", " ".repeat(self.0 + 2), ident);
@@ -111,11 +158,42 @@ impl Visitor for Printer {
                 self.visit_block(good)?;
                 self.visit_block(bad)?;
             }
-            Stmt::While(pred, block) => {
-                println!("while");
+            Stmt::While(label, pred, block, inc) => {
+                match label {
+                    Some(label) => println!("while ({})", label),
+                    None => println!("while"),
+                }
                 self.visit_expr(pred)?;
                 self.visit_block(block)?;
+
+                if let Some(inc) = inc {
+                    self.visit_expr(inc)?;
+                }
             }
+            Stmt::DoWhile(label, block, pred) => {
+                match label {
+                    Some(label) => println!("do while ({})", label),
+                    None => println!("do while"),
+                }
+                self.visit_block(block)?;
+                self.visit_expr(pred)?;
+            }
+            Stmt::ForIn(label, item, iterable, block) => {
+                match label {
+                    Some(label) => println!("for {} in ({})", item, label),
+                    None => println!("for {} in", item),
+                }
+                self.visit_expr(iterable)?;
+                self.visit_block(block)?;
+            }
+            Stmt::Break(label) => match label {
+                Some(label) => println!("break {}", label),
+                None => println!("break"),
+            },
+            Stmt::Continue(label) => match label {
+                Some(label) => println!("continue {}", label),
+                None => println!("continue"),
+            },
             Stmt::Block(s) => {
                 println!("block");
                 for decl in &mut s.0 {
@@ -133,6 +211,20 @@ impl Visitor for Printer {
                     self.visit_block(&mut body)?;
                 } 
             }
+            Stmt::Class(name, superclass, methods) => {
+                match superclass {
+                    Some(superclass) => println!("<class {} < {} ({} methods)>", name, superclass, methods.len()),
+                    None => println!("<class {} ({} methods)>", name, methods.len()),
+                }
+
+                for method in methods.iter() {
+                    if let Some(user) = (*method.borrow()).downcast_ref::<UserFn>() {
+                        let mut body = user.body.clone();
+                        println!("{}<method {} ({})>", " ".repeat(self.0 + 2), user.name(), user.arity());
+                        self.visit_block(&mut body)?;
+                    }
+                }
+            }
         }
 
         self.0 -= 2;
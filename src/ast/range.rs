@@ -0,0 +1,135 @@
+use crate::ast::function::{self, Body};
+use crate::ast::operator::BinOp;
+use crate::ast::{Ident, Object};
+use crate::error::Error;
+use crate::interpreter::{Exec, Interpreter};
+
+use std::convert::TryFrom;
+use std::fmt;
+
+/// `start..end step n`, counting from `start` towards `end` by `step`. Ranges
+/// are values: stepping one builds a new range.
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct Range {
+    pub start: isize,
+    pub end: isize,
+    pub step: isize,
+    pub inclusive: bool,
+}
+
+impl Range {
+    /// Whether `i` lies between the range's bounds, in the direction it counts.
+    fn within(&self, i: isize) -> bool {
+        match (self.step > 0, self.inclusive) {
+            (true, true) => self.start <= i && i <= self.end,
+            (true, false) => self.start <= i && i < self.end,
+            (false, true) => self.end <= i && i <= self.start,
+            (false, false) => self.end < i && i <= self.start,
+        }
+    }
+
+    pub fn first(&self) -> Option<isize> {
+        Some(self.start).filter(|&i| self.within(i))
+    }
+
+    /// The item after `i`, if it's still in the range.
+    pub fn after(&self, i: isize) -> Option<isize> {
+        i.checked_add(self.step).filter(|&next| self.within(next))
+    }
+
+    pub fn contains(&self, i: isize) -> bool {
+        self.within(i) && (i as i128 - self.start as i128) % self.step as i128 == 0
+    }
+
+    /// How many items the range has, which may be more than an int can hold.
+    pub fn len(&self) -> Result<isize, Error> {
+        let len = match self.first() {
+            Some(_) => {
+                let last = if self.inclusive { self.end as i128 } else { self.end as i128 - self.step.signum() as i128 };
+                (last - self.start as i128) / self.step as i128 + 1
+            }
+            None => 0,
+        };
+
+        isize::try_from(len).map_err(|_| Error::RangeTooLong(self.to_string()))
+    }
+}
+
+impl fmt::Display for Range {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let op = if self.inclusive { BinOp::RangeInclusive } else { BinOp::Range };
+        write!(f, "{}{}{}", self.start, op, self.end)?;
+
+        if self.step != 1 {
+            write!(f, " step {}", self.step)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// `lhs..rhs` or `lhs..=rhs`, counting up by one.
+pub fn new(lhs: &Object, rhs: &Object, inclusive: bool) -> Result<Object, Error> {
+    let bound = |bound: &Object| {
+        isize::try_from(bound.clone()).map_err(|_| Error::InvalidRangeBound(bound.type_name()))
+    };
+
+    Ok(Object::Range(Range {
+        start: bound(lhs)?,
+        end: bound(rhs)?,
+        step: 1,
+        inclusive,
+    }))
+}
+
+/// `range step n`, where a negative `n` counts down.
+pub fn step(range: &Object, step: &Object) -> Result<Object, Error> {
+    let range = match range {
+        Object::Range(range) => range,
+        other => return Err(Error::InvalidBinaryOperator(other.to_string(), BinOp::Step, step.to_string())),
+    };
+
+    match isize::try_from(step.clone()) {
+        Ok(0) => Err(Error::ZeroStep),
+        Ok(step) => Ok(Object::Range(Range { step, ..range.clone() })),
+        Err(_) => Err(Error::InvalidRangeBound(step.type_name())),
+    }
+}
+
+/// The method `range.name`, if there is one.
+pub fn get_method(range: &Range, name: &Ident) -> Option<Object> {
+    let (args, body): (&[&str], Body) = match name.0.as_str() {
+        "len" => (&[], len),
+        "contains" => (&["item"], contains),
+        _ => return None,
+    };
+
+    Some(function::bind_native(Object::Range(range.clone()), name, args, body))
+}
+
+fn this(interpreter: &Interpreter) -> Result<Range, Error> {
+    Range::try_from(interpreter.this()?)
+}
+
+fn len(interpreter: &mut Interpreter, _: &[Object]) -> Result<Exec, Error> {
+    let len = this(interpreter)?.len()?;
+    Ok(Exec::Value(Object::Int(len)))
+}
+
+fn contains(interpreter: &mut Interpreter, args: &[Object]) -> Result<Exec, Error> {
+    let found = match args[0] {
+        Object::Int(i) => this(interpreter)?.contains(i),
+        _ => false,
+    };
+
+    Ok(Exec::Value(Object::Bool(found)))
+}
+
+/// Ranges are equal when they produce the same numbers the same way.
+pub fn binop(lhs: &Range, op: BinOp, rhs: &Range) -> Result<Object, Error> {
+    match op {
+        BinOp::EqEq => Ok(Object::Bool(lhs == rhs)),
+        BinOp::NotEq => Ok(Object::Bool(lhs != rhs)),
+        _ => Err(Error::InvalidBinaryOperator(lhs.to_string(), op, rhs.to_string())),
+    }
+}
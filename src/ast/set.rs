@@ -0,0 +1,132 @@
+use crate::ast::function::{self, Body};
+use crate::ast::map::Key;
+use crate::ast::operator::BinOp;
+use crate::ast::{list, Ident, Object};
+use crate::error::Error;
+use crate::interpreter::{Exec, Interpreter};
+
+use derive_more::{Deref, DerefMut};
+use indexmap::IndexSet;
+
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::rc::Rc;
+
+/// Sets are shared like lists, and hold the same kinds of values as map keys.
+pub type Set = Rc<RefCell<Members>>;
+
+/// A set's members in insertion order.
+#[derive(Debug, Default, Clone, PartialEq, Deref, DerefMut)]
+pub struct Members(pub IndexSet<Key>);
+
+// Sets aren't ordered by `<`, but `Object` needs an ordering
+impl PartialOrd for Members {
+    fn partial_cmp(&self, _: &Self) -> Option<Ordering> {
+        None
+    }
+}
+
+pub fn new(members: IndexSet<Key>) -> Object {
+    Object::Set(Rc::new(RefCell::new(Members(members))))
+}
+
+/// Build a set from the items of a list, or copy another set.
+pub fn from_object(items: &Object) -> Result<Object, Error> {
+    match items {
+        Object::List(list) => {
+            let members = list.borrow().iter().cloned().map(Key::new).collect::<Result<_, _>>()?;
+            Ok(new(members))
+        }
+        Object::Set(set) => Ok(new(set.borrow().0.clone())),
+        other => Err(Error::TypeMismatch(other.to_string(), "list".into())),
+    }
+}
+
+/// `set([1, "a"])`, the call that would build the same set.
+pub fn display(set: &Set) -> String {
+    let members: Vec<String> = set.borrow().iter().map(|key| list::repr(key.object())).collect();
+    format!("set([{}])", members.join(", "))
+}
+
+/// The method `set.name`, if there is one.
+pub fn get_method(set: &Set, name: &Ident) -> Option<Object> {
+    let (args, body): (&[&str], Body) = match name.0.as_str() {
+        "len" => (&[], len),
+        "add" => (&["item"], add),
+        "remove" => (&["item"], remove),
+        "contains" => (&["item"], contains),
+        "union" => (&["other"], union),
+        "intersection" => (&["other"], intersection),
+        "difference" => (&["other"], difference),
+        "items" => (&[], items),
+        _ => return None,
+    };
+
+    Some(function::bind_native(Object::Set(set.clone()), name, args, body))
+}
+
+fn this(interpreter: &Interpreter) -> Result<Set, Error> {
+    Set::try_from(interpreter.this()?)
+}
+
+fn len(interpreter: &mut Interpreter, _: &[Object]) -> Result<Exec, Error> {
+    let len = this(interpreter)?.borrow().len();
+    Ok(Exec::Value(Object::Int(len as isize)))
+}
+
+fn add(interpreter: &mut Interpreter, args: &[Object]) -> Result<Exec, Error> {
+    let item = Key::new(args[0].clone())?;
+    this(interpreter)?.borrow_mut().insert(item);
+    Ok(Exec::Value(Object::Nil))
+}
+
+/// Remove `item`, returning whether it was in the set.
+fn remove(interpreter: &mut Interpreter, args: &[Object]) -> Result<Exec, Error> {
+    let item = Key::new(args[0].clone())?;
+    let removed = this(interpreter)?.borrow_mut().shift_remove(&item);
+    Ok(Exec::Value(Object::Bool(removed)))
+}
+
+fn contains(interpreter: &mut Interpreter, args: &[Object]) -> Result<Exec, Error> {
+    let item = Key::new(args[0].clone())?;
+    let found = this(interpreter)?.borrow().contains(&item);
+    Ok(Exec::Value(Object::Bool(found)))
+}
+
+fn union(interpreter: &mut Interpreter, args: &[Object]) -> Result<Exec, Error> {
+    method_binop(interpreter, BinOp::BitOr, &args[0])
+}
+
+fn intersection(interpreter: &mut Interpreter, args: &[Object]) -> Result<Exec, Error> {
+    method_binop(interpreter, BinOp::BitAnd, &args[0])
+}
+
+fn difference(interpreter: &mut Interpreter, args: &[Object]) -> Result<Exec, Error> {
+    method_binop(interpreter, BinOp::Minus, &args[0])
+}
+
+fn method_binop(interpreter: &Interpreter, op: BinOp, other: &Object) -> Result<Exec, Error> {
+    let other = Set::try_from(other.clone())?;
+    Ok(Exec::Value(binop(&this(interpreter)?, op, &other)?))
+}
+
+fn items(interpreter: &mut Interpreter, _: &[Object]) -> Result<Exec, Error> {
+    let items = this(interpreter)?.borrow().iter().map(|key| key.object().clone()).collect();
+    Ok(Exec::Value(list::new(items)))
+}
+
+/// `|` is union, `&` intersection and `-` difference. `==` ignores the order
+/// members were added in.
+pub fn binop(lhs: &Set, op: BinOp, rhs: &Set) -> Result<Object, Error> {
+    let (l, r) = (lhs.borrow(), rhs.borrow());
+
+    match op {
+        BinOp::BitOr => Ok(new(l.union(&r).cloned().collect())),
+        BinOp::BitAnd => Ok(new(l.intersection(&r).cloned().collect())),
+        BinOp::Minus => Ok(new(l.difference(&r).cloned().collect())),
+        BinOp::EqEq => Ok(Object::Bool(*l == *r)),
+        BinOp::NotEq => Ok(Object::Bool(*l != *r)),
+        _ => Err(Error::InvalidBinaryOperator(display(lhs), op, display(rhs))),
+    }
+}
@@ -37,6 +37,15 @@ where
         walk_func(self, name, func)
     }
 
+    fn visit_class(
+        &mut self,
+        name: &mut Ident,
+        superclass: &mut Option<Ident>,
+        methods: &mut [Func],
+    ) -> Result<Self::Output, Error> {
+        walk_class(self, name, superclass, methods)
+    }
+
     fn visit_if(
         &mut self,
         check: &mut Expr,
@@ -57,11 +66,43 @@ where
         walk_block(self, block)
     }
 
-    fn visit_while(&mut self, pred: &mut Expr, block: &mut Block) -> Result<Self::Output, Error> {
+    fn visit_while(
+        &mut self,
+        _label: &mut Option<Ident>,
+        pred: &mut Expr,
+        block: &mut Block,
+        inc: &mut Option<Expr>,
+    ) -> Result<Self::Output, Error> {
         // println!("vw");
+        let res = walk_while(self, pred, block)?;
+
+        if let Some(inc) = inc {
+            self.visit_expr(inc)?;
+        }
+
+        Ok(res)
+    }
+
+    fn visit_do_while(
+        &mut self,
+        _label: &mut Option<Ident>,
+        block: &mut Block,
+        pred: &mut Expr,
+    ) -> Result<Self::Output, Error> {
         walk_while(self, pred, block)
     }
 
+    fn visit_for_in(
+        &mut self,
+        _label: &mut Option<Ident>,
+        _item: &mut Ident,
+        iterable: &mut Expr,
+        block: &mut Block,
+    ) -> Result<Self::Output, Error> {
+        self.visit_expr(iterable)?;
+        self.visit_block(block)
+    }
+
     // fn finish_expr(&mut self, _e: &mut Expr, res: Result<Self::Output, Error>) -> Result<Self::Output, Error> {
     //     res
     // }
@@ -136,13 +177,13 @@ pub fn walk_program<V: Visitor>(
     visitor: &mut V,
     program: &mut Program,
 ) -> Result<V::Output, Error> {
-    let mut res = program
-        .decls
-        .iter_mut()
-        .map(|d| visitor.visit_decl(d))
-        .collect::<Vec<Result<V::Output, Error>>>();
+    let mut last = V::Output::default();
 
-    res.pop().unwrap()
+    for decl in program.decls.iter_mut() {
+        last = visitor.visit_decl(decl)?;
+    }
+
+    Ok(last)
 }
 
 pub fn walk_decl<V: Visitor>(visitor: &mut V, decl: &mut Decl) -> Result<V::Output, Error> {
@@ -162,12 +203,16 @@ pub fn walk_stmt<V: Visitor>(visitor: &mut V, stmt: &mut Stmt) -> Result<V::Outp
                 Ok(V::Output::default())
             }
         }
-        Stmt::Expr(e) | Stmt::Print(e) => visitor.visit_expr(e),
+        Stmt::Expr(e) | Stmt::Print(e) | Stmt::Yield(e) => visitor.visit_expr(e),
         Stmt::Block(decls) => visitor.visit_block(decls),
         Stmt::VarDecl(ident, init) => visitor.visit_var_decl(ident, init),
         Stmt::If(c, g, b) => visitor.visit_if(c, g, b),
-        Stmt::While(pred, block) => visitor.visit_while(pred, block),
+        Stmt::While(label, pred, block, inc) => visitor.visit_while(label, pred, block, inc),
+        Stmt::DoWhile(label, block, pred) => visitor.visit_do_while(label, block, pred),
+        Stmt::ForIn(label, item, iterable, block) => visitor.visit_for_in(label, item, iterable, block),
+        Stmt::Break(_) | Stmt::Continue(_) => Ok(V::Output::default()),
         Stmt::Func(name, func) => visitor.visit_func(name, func.clone()),
+        Stmt::Class(name, superclass, methods) => visitor.visit_class(name, superclass, methods),
     }
 }
 
@@ -212,6 +257,15 @@ pub fn walk_func<V: Visitor>(
 ) -> Result<V::Output, Error> {
     Ok(V::Output::default())
 }
+
+pub fn walk_class<V: Visitor>(
+    _visitor: &mut V,
+    _name: &mut Ident,
+    _superclass: &mut Option<Ident>,
+    _methods: &mut [Func],
+) -> Result<V::Output, Error> {
+    Ok(V::Output::default())
+}
 // pub trait Visitable {
 //     fn visit<T>(&mut self, v: &mut dyn Visitor<T>) -> Result<Self::Output, Error>;
 // }
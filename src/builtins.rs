@@ -0,0 +1,55 @@
+use std::cell::RefCell;
+use std::convert::TryFrom;
+use std::rc::Rc;
+
+use crate::ast::function::{Body, BuiltinFn};
+use crate::ast::{set, Ident, Object};
+use crate::error::Error;
+use crate::interpreter::{Exec, Interpreter};
+
+/// Define every native function in `interpreter`'s global scope.
+pub fn define_builtins(interpreter: &mut Interpreter) {
+    let builtins: &[(&str, &[&str], Body)] = &[
+        ("wrapping_add", &["a", "b"], wrapping_add),
+        ("wrapping_sub", &["a", "b"], wrapping_sub),
+        ("wrapping_mul", &["a", "b"], wrapping_mul),
+        ("set", &["items"], new_set),
+    ];
+
+    for (name, args, body) in builtins {
+        let args: Vec<Ident> = args.iter().map(|a| Ident(a.to_string())).collect();
+        let func = BuiltinFn::new(args.len(), args, Ident(name.to_string()), Vec::new(), *body);
+
+        interpreter.define_global(
+            Ident(name.to_string()),
+            Object::Func(Rc::new(RefCell::new(Box::new(func)))),
+        );
+    }
+}
+
+fn ints(args: &[Object]) -> Result<(isize, isize), Error> {
+    Ok((isize::try_from(args[0].clone())?, isize::try_from(args[1].clone())?))
+}
+
+/// `a + b`, wrapping around on overflow instead of raising an error.
+fn wrapping_add(_: &mut Interpreter, args: &[Object]) -> Result<Exec, Error> {
+    let (a, b) = ints(args)?;
+    Ok(Exec::Value(a.wrapping_add(b).into()))
+}
+
+/// `a - b`, wrapping around on overflow instead of raising an error.
+fn wrapping_sub(_: &mut Interpreter, args: &[Object]) -> Result<Exec, Error> {
+    let (a, b) = ints(args)?;
+    Ok(Exec::Value(a.wrapping_sub(b).into()))
+}
+
+/// `a * b`, wrapping around on overflow instead of raising an error.
+fn wrapping_mul(_: &mut Interpreter, args: &[Object]) -> Result<Exec, Error> {
+    let (a, b) = ints(args)?;
+    Ok(Exec::Value(a.wrapping_mul(b).into()))
+}
+
+/// A set of the distinct items in a list.
+fn new_set(_: &mut Interpreter, args: &[Object]) -> Result<Exec, Error> {
+    Ok(Exec::Value(set::from_object(&args[0])?))
+}
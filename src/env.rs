@@ -1,66 +1,84 @@
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::mem;
+use std::rc::Rc;
 
 use derive_more::{Deref, DerefMut};
 
 use crate::ast::{Ident, Object};
 use crate::error::Error;
 
-pub type Closure = HashMap<Ident, Object>;
+pub type Scope = Rc<RefCell<HashMap<Ident, Object>>>;
+
+/// The chain of scopes a function was defined in. Scopes are shared, so a closure
+/// sees (and makes) later changes to the variables it captured.
+pub type Closure = Vec<Scope>;
 
 #[derive(Default, Debug, Clone, PartialEq, Deref, DerefMut)]
 pub struct Environment {
     // pub globals: HashMap<Ident, Object>,
-    pub vars: Vec<HashMap<Ident, Object>>,
+    pub vars: Vec<Scope>,
 }
 
 impl Environment {
     pub fn new() -> Self {
         Self {
-            vars: vec![HashMap::default()],
+            vars: vec![Scope::default()],
         }
     }
 
-    pub fn last(&self) -> &HashMap<Ident, Object> {
-        &self.vars.last().unwrap()
+    pub fn last(&self) -> &Scope {
+        self.vars.last().unwrap()
     }
 
-    pub fn last_mut(&mut self) -> &mut HashMap<Ident, Object> {
-        self.vars.last_mut().unwrap()
+    pub fn define(&mut self, ident: Ident, value: Object) {
+        self.last().borrow_mut().insert(ident, value);
     }
 
-    pub fn define(&mut self, ident: Ident, value: Object) {
-        self.last_mut().insert(ident, value);
+    /// Define `ident` in the outermost scope.
+    pub fn define_global(&mut self, ident: Ident, value: Object) {
+        self.vars[0].borrow_mut().insert(ident, value);
     }
 
     pub fn get(&self, ident: &Ident) -> Result<Object, Error> {
         self.vars
             .iter()
             .rev()
-            .find(|e| e.contains_key(ident))
-            .map(|e| e[ident].clone())
+            .find_map(|e| e.borrow().get(ident).cloned())
             .ok_or(Error::UndefinedVariable(ident.clone()))
     }
 
     pub fn set(&mut self, ident: &Ident, value: Object) -> Result<Object, Error> {
-        let v: &mut HashMap<Ident, Object> = self
+        let scope: &Scope = self
             .vars
-            .iter_mut()
+            .iter()
             .rev()
-            .find(|e| e.contains_key(ident))
+            .find(|e| e.borrow().contains_key(ident))
             .ok_or(Error::UndefinedVariable(ident.clone()))?;
-        *v.get_mut(ident).unwrap() = value.clone();
+        scope.borrow_mut().insert(ident.clone(), value.clone());
         Ok(value)
     }
 
+    /// The current chain of scopes, for a function being defined here.
+    pub fn capture(&self) -> Closure {
+        self.vars.clone()
+    }
+
     pub fn push_scope(&mut self) {
         self.vars.push(Default::default());
     }
 
-    pub fn push_closure(&mut self, closure: Closure) {
-        self.vars.push(closure);
+    /// Switch to the scopes of `closure`, returning the caller's scopes.
+    pub fn push_closure(&mut self, closure: Closure) -> Closure {
+        mem::replace(&mut self.vars, closure)
+    }
+
+    /// Return to the caller's scopes after a `push_closure`.
+    pub fn pop_closure(&mut self, caller: Closure) {
+        self.vars = caller;
     }
 
-    pub fn pop_scope(&mut self) -> Closure {
+    pub fn pop_scope(&mut self) -> Scope {
         self.vars.pop().unwrap()
     }
 }
@@ -1,16 +1,28 @@
 use crate::ast::operator::{BinOp, UnOp};
 use crate::ast::Ident;
+use crate::parser::Rule;
 use failure::Fail;
+use pest::error::Error as PestError;
 use std::io::Error as IOError;
 
 #[derive(Debug, Fail)]
 pub enum Error {
     #[fail(display = "An IO Error was encountered: {:?}", 0)]
     IOError(#[cause] IOError),
+    #[fail(display = "Syntax Error:\n{}", 0)]
+    Syntax(PestError<Rule>),
     #[fail(display = "Type Mismatch: attempted to convert `{}` into `{}`", 0, 1)]
     TypeMismatch(String, String),
     #[fail(display = "Invalid Operator: {} {} {}", 0, 1, 2)]
     InvalidBinaryOperator(String, BinOp, String),
+    #[fail(display = "Unsupported operand types for `{}`: {} and {}", 0, 1, 2)]
+    MismatchedOperands(BinOp, &'static str, &'static str),
+    #[fail(display = "Division or modulo by zero")]
+    DivisionByZero,
+    #[fail(display = "Negative shift count `{}`", 0)]
+    NegativeShift(String),
+    #[fail(display = "`{}` is too large for the right side of `{}`", 0, 1)]
+    OperandTooLarge(String, BinOp),
     #[fail(display = "Invalid Operator: {} {}", 0, 1)]
     InvalidUnaryOperator(UnOp, String),
     #[fail(display = "Expected Value")]
@@ -26,6 +38,63 @@ pub enum Error {
         0, 1
     )]
     ArgumentArity(usize, usize),
+    #[fail(display = "Can only call functions and classes, found `{}`", 0)]
+    NotCallable(String),
+    #[fail(display = "Superclass must be a class, found `{}`", 0)]
+    InvalidSuperclass(String),
+    #[fail(display = "A class can't inherit from itself: `{}`", 0)]
+    InheritsFromSelf(Ident),
+    #[fail(display = "Undefined property `{}`", 0)]
+    UndefinedProperty(Ident),
+    #[fail(display = "Index `{}` is out of range for a list of length `{}`", 0, 1)]
+    IndexOutOfRange(String, usize),
+    #[fail(display = "Can't pop from an empty list")]
+    PopFromEmpty,
+    #[fail(display = "List indices must be ints, found {}", 0)]
+    InvalidIndex(&'static str),
+    #[fail(display = "Key {} is not in the map", 0)]
+    MissingKey(String),
+    #[fail(display = "Only strings, ints, bools and nil can be map keys or set members, found {}", 0)]
+    Unhashable(&'static str),
+    #[fail(display = "Range bounds and steps must be ints, found {}", 0)]
+    InvalidRangeBound(&'static str),
+    #[fail(display = "A range's step can't be zero")]
+    ZeroStep,
+    #[fail(display = "The range `{}` has too many items to count with an int", 0)]
+    RangeTooLong(String),
+    #[fail(display = "Can't iterate over {}", 0)]
+    NotIterable(&'static str),
+    #[fail(display = "The iterator has no more items")]
+    IteratorExhausted,
+    #[fail(display = "Can't advance a generator from inside its own body")]
+    GeneratorRunning,
+    #[fail(display = "Can't use `yield` outside of a function")]
+    YieldOutsideGenerator,
+    #[fail(display = "Stack overflow: calls nested more than {} deep", 0)]
+    StackOverflow(usize),
+    #[fail(display = "Can't index into {}", 0)]
+    NotIndexable(&'static str),
+    #[fail(display = "Only instances have properties, found `{}`", 0)]
+    NotAnInstance(String),
+    #[fail(display = "Can't use `super` outside of a class with a superclass")]
+    SuperOutsideSubclass,
+    #[fail(display = "Can't use `break` outside of a loop")]
+    BreakOutsideLoop,
+    #[fail(display = "Can't use `continue` outside of a loop")]
+    ContinueOutsideLoop,
+    #[fail(display = "Undefined loop label `{}`", 0)]
+    UndefinedLabel(Ident),
+}
+
+impl Error {
+    /// The process exit code for this error, following the `sysexits.h` convention.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::Syntax(_) => 65,
+            Error::IOError(_) => 74,
+            _ => 70,
+        }
+    }
 }
 
 impl From<IOError> for Error {
@@ -33,3 +102,9 @@ impl From<IOError> for Error {
         Error::IOError(error)
     }
 }
+
+impl From<PestError<Rule>> for Error {
+    fn from(error: PestError<Rule>) -> Error {
+        Error::Syntax(error)
+    }
+}
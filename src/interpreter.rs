@@ -1,14 +1,28 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::rc::Rc;
 use std::convert::TryInto;
 
+use indexmap::IndexMap;
+use corosensei::Yielder;
+use num_bigint::BigInt;
+use num_traits::ToPrimitive;
+
+use crate::ast::class::{self, Class, Instance, LoxClass};
+use crate::ast::list::{self, List};
+use crate::ast::map::{self, Key, Map};
+use crate::ast::iter;
+use crate::ast::range;
+use crate::ast::set;
 use crate::ast::function::LoxFn;
 use crate::ast::visit::*;
 use crate::ast::{
-    operator::{BinOp, BinaryOp, UnOp, UnaryOp},
+    operator::{self, BinOp, BinaryOp, Fixity, UnOp, UnaryOp},
     Block, Decl, Expr, Ident, Object, Program, Stmt, Func,
 };
-use crate::env::{Environment, Closure};
+use crate::builtins;
+use crate::env::{Closure, Environment, Scope};
 use crate::ast::function::{BuiltinFn, UserFn};
 
 use crate::error::Error;
@@ -17,8 +31,8 @@ macro_rules! value {
     ($name:expr) => {
         match $name {
             Exec::Value(o) => o,
-            r @ Exec::Return(_) => return Ok(r),
-            _ => panic!(),
+            r @ Exec::Return(_) | r @ Exec::Break(_) | r @ Exec::Continue(_) => return Ok(r),
+            Exec::None => return Err(Error::ExpectedValue),
         }
     };
 }
@@ -27,7 +41,8 @@ macro_rules! catch {
     ($name:expr) => {
         match $name {
             Exec::Return(c) | Exec::Value(c) => Exec::Value(c),
-            _ => panic!(),
+            Exec::None => Exec::Value(Object::Nil),
+            other => return Err(escaped_loop(other)),
         }
     }
 }
@@ -48,37 +63,217 @@ macro_rules! catch {
 //     };
 // }
 
+/// How deeply Lox calls may nest before raising an error. Every call recurses
+/// on the native stack, so this has to run out well before `STACK_SIZE` does.
+pub const MAX_CALL_DEPTH: usize = 1000;
+
+/// The native stack to give the interpreter and each generator.
+pub const STACK_SIZE: usize = 256 * 1024 * 1024;
+
 #[derive(Default, Debug, Clone, PartialEq)]
 pub struct Interpreter {
     env: Environment,
     call_stack: Vec<Ident>,
+    /// Where `yield` sends values, while running a generator's body.
+    yielder: Option<*const Yielder<(), Object>>,
 }
 
 impl Interpreter {
     pub fn new() -> Self {
-        Self {
+        let mut interpreter = Self {
             env: Environment::new(),
             call_stack: Vec::new(),
+            yielder: None,
+        };
+
+        builtins::define_builtins(&mut interpreter);
+        interpreter
+    }
+
+    /// An interpreter for a generator's body, running in `closure`.
+    pub(crate) fn for_generator(closure: Closure) -> Self {
+        Self {
+            env: Environment { vars: closure },
+            call_stack: Vec::new(),
+            yielder: None,
         }
     }
 
-    pub fn define_global(&mut self, _name: Ident, _value: Object) {}
+    /// Run a generator's body, handing each `yield`ed value to `yielder`.
+    pub(crate) fn run_generator(&mut self, body: &mut Block, yielder: &Yielder<(), Object>) -> Result<(), Error> {
+        self.yielder = Some(yielder);
+        let result = self.visit_block(body);
+        self.yielder = None;
+
+        result.map(|_| ())
+    }
+
+    pub fn define_global(&mut self, name: Ident, value: Object) {
+        self.env.define_global(name, value);
+    }
 
-    pub fn push_closure(&mut self, closure: Closure) {
+    /// Run in the scopes of `closure` until the matching `pop_closure`.
+    pub fn push_closure(&mut self, closure: Closure) -> Closure {
         self.env.push_closure(closure)
     }
 
+    pub fn pop_closure(&mut self, caller: Closure) {
+        self.env.pop_closure(caller)
+    }
+
     pub fn push_scope(&mut self) {
         self.env.push_scope();
     }
 
-    pub fn pop_scope(&mut self) -> Closure {
+    pub fn pop_scope(&mut self) -> Scope {
         self.env.pop_scope()
     }
 
+    /// A fresh copy of a declared function that closes over the current scopes.
+    fn capture(&self, func: &Func) -> Func {
+        match (*func.borrow()).downcast_ref::<UserFn>() {
+            Some(user) => Rc::new(RefCell::new(Box::new(user.with_closure(self.env.capture())))),
+            None => func.clone(),
+        }
+    }
+
     pub fn define(&mut self, name: Ident, value: Object) {
         self.env.define(name, value);
     }
+
+    /// The receiver of the method being run.
+    pub fn this(&self) -> Result<Object, Error> {
+        self.env.get(&Ident("this".into()))
+    }
+
+    /// Call `callee` from native code, for the value it returns.
+    pub fn call_value(&mut self, callee: Object, args: &mut [Object]) -> Result<Object, Error> {
+        match catch!(self.call(callee, args)?) {
+            Exec::Value(value) => Ok(value),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Call a function or construct an instance of a class.
+    pub fn call(&mut self, callee: Object, args: &mut [Object]) -> Result<Exec, Error> {
+        if self.call_stack.len() >= MAX_CALL_DEPTH {
+            return Err(Error::StackOverflow(MAX_CALL_DEPTH));
+        }
+
+        let name = match &callee {
+            Object::Func(func) => Ident(func.borrow().name().to_string()),
+            Object::Class(class) => class.name.clone(),
+            other => return Err(Error::NotCallable(other.to_string())),
+        };
+
+        self.call_stack.push(name);
+        let result = match callee {
+            Object::Func(func) => self.visit_func_call(func, args),
+            Object::Class(class) => LoxClass::instantiate(&class, self, args),
+            _ => unreachable!(),
+        };
+        self.call_stack.pop();
+        result
+    }
+
+    /// Resolve `super.name` against the superclass of the enclosing method,
+    /// bound to the current `this`.
+    fn super_method(&mut self, name: &Ident) -> Result<Option<Object>, Error> {
+        let superclass: Class = self
+            .env
+            .get(&Ident("super".into()))
+            .map_err(|_| Error::SuperOutsideSubclass)?
+            .try_into()?;
+        let instance: Instance = self.env.get(&Ident("this".into()))?.try_into()?;
+
+        Ok(superclass
+            .find_method(name)
+            .map(|method| Object::Func(class::bind(&method, instance))))
+    }
+
+    /// Evaluate `lhs.name`, where `lhs` is an instance, a collection, or `super`.
+    fn property(&mut self, lhs: &mut Expr, name: &Ident) -> Result<Object, Error> {
+        let property = match lhs {
+            Expr::Object(Object::Ident(Ident(ident))) if ident == "super" => self.super_method(name)?,
+            _ => {
+                let object = self.eval(lhs)?;
+                return self.method(&object, name);
+            }
+        };
+
+        property.ok_or_else(|| Error::UndefinedProperty(name.clone()))
+    }
+
+    /// Look up `object.name`, binding methods to `object`.
+    pub(crate) fn method(&self, object: &Object, name: &Ident) -> Result<Object, Error> {
+        let property = match object {
+            Object::Instance(instance) => class::get_property(instance, name),
+            _ if name.0 == "iter" && iter::is_builtin_iterable(object) => iter::iter_method(object.clone()),
+            Object::List(list) => list::get_method(list, name),
+            Object::Map(map) => map::get_method(map, name),
+            Object::Set(set) => set::get_method(set, name),
+            Object::Range(range) => range::get_method(range, name),
+            Object::Iter(iter) => iter::get_method(iter, name),
+            other => return Err(Error::NotAnInstance(other.to_string())),
+        };
+
+        property.ok_or_else(|| Error::UndefinedProperty(name.clone()))
+    }
+
+    /// Evaluate the object part of an assignment target, so that compound
+    /// assignments only evaluate it once.
+    fn place(&mut self, target: &mut Expr) -> Result<Place, Error> {
+        match target {
+            Expr::Object(Object::Ident(ident)) => Ok(Place::Var(ident.clone())),
+            Expr::Access(object, name) => Ok(Place::Field(self.eval_instance(object)?, name.clone())),
+            Expr::Index(container, index) => match self.eval(container)? {
+                Object::List(list) => Ok(Place::Index(list, self.eval(index)?)),
+                Object::Map(map) => Ok(Place::Key(map, Key::new(self.eval(index)?)?)),
+                other => Err(Error::NotIndexable(other.type_name())),
+            },
+            _ => Err(Error::UnsupportedOperation(
+                "Only variables and properties can be assigned.".to_string(),
+            )),
+        }
+    }
+
+    fn load(&mut self, place: &Place) -> Result<Object, Error> {
+        match place {
+            Place::Var(ident) => self.env.get(ident),
+            Place::Field(instance, name) => class::get_property(instance, name)
+                .ok_or_else(|| Error::UndefinedProperty(name.clone())),
+            Place::Index(list, index) => list::get(list, index),
+            Place::Key(map, key) => map::get(map, key.object().clone()),
+        }
+    }
+
+    fn store(&mut self, place: Place, value: Object) -> Result<Object, Error> {
+        match place {
+            Place::Var(ident) => self.env.set(&ident, value),
+            Place::Field(instance, name) => {
+                class::set_property(&instance, name, value.clone());
+                Ok(value)
+            }
+            Place::Index(list, index) => list::set(&list, &index, value),
+            Place::Key(map, key) => map::set(&map, key, value),
+        }
+    }
+
+    /// Evaluate an expression that can't `return`, `break` or `continue`.
+    fn eval(&mut self, e: &mut Expr) -> Result<Object, Error> {
+        match self.visit_expr(e)? {
+            Exec::Value(value) => Ok(value),
+            _ => Err(Error::ExpectedValue),
+        }
+    }
+
+    fn eval_instance(&mut self, e: &mut Expr) -> Result<Instance, Error> {
+        match self.visit_expr(e)? {
+            Exec::Value(Object::Instance(instance)) => Ok(instance),
+            Exec::Value(other) => Err(Error::NotAnInstance(other.to_string())),
+            _ => Err(Error::ExpectedValue),
+        }
+    }
 }
 
 impl Visitor for Interpreter {
@@ -89,7 +284,10 @@ impl Visitor for Interpreter {
         f: Func,
         args: &mut [Object],
     ) -> Result<Self::Output, Error> {
-        // self.call_stack.push(Ident(f.borrow().name().to_string()));
+        let arity = f.borrow().arity();
+        if arity != args.len() {
+            return Err(Error::ArgumentArity(arity, args.len()));
+        }
 
         f.borrow().call(self, args)
     }
@@ -99,11 +297,47 @@ impl Visitor for Interpreter {
         name: &mut Ident,
         func: Func,
     ) -> Result<Self::Output, Error> {
-        if let Some(user) = (*func.borrow()).downcast_ref::<UserFn>() {
-            user.set_closure(self.env.last().clone());
-        } 
+        let func = self.capture(&func);
         // println!("[FUNC] {:?}", func);
-        self.env.define(name.clone(), Object::Func(func.clone()));
+        self.env.define(name.clone(), Object::Func(func));
+        Ok(Exec::None)
+    }
+
+    fn visit_class(
+        &mut self,
+        name: &mut Ident,
+        superclass: &mut Option<Ident>,
+        methods: &mut [Func],
+    ) -> Result<Self::Output, Error> {
+        let superclass: Option<Class> = match superclass {
+            Some(superclass) if superclass == name => {
+                return Err(Error::InheritsFromSelf(superclass.clone()))
+            }
+            Some(superclass) => match self.env.get(superclass)? {
+                Object::Class(class) => Some(class),
+                other => return Err(Error::InvalidSuperclass(other.to_string())),
+            },
+            None => None,
+        };
+
+        let mut closure = self.env.capture();
+        if let Some(superclass) = &superclass {
+            let scope: Scope = Default::default();
+            scope.borrow_mut().insert(Ident("super".into()), Object::Class(superclass.clone()));
+            closure.push(scope);
+        }
+
+        let mut method_table = HashMap::new();
+
+        for method in methods.iter() {
+            if let Some(user) = (*method.borrow()).downcast_ref::<UserFn>() {
+                let method: Func = Rc::new(RefCell::new(Box::new(user.with_closure(closure.clone()))));
+                method_table.insert(user.name.clone(), method);
+            }
+        }
+
+        let class: Class = Rc::new(LoxClass::new(name.clone(), superclass, method_table));
+        self.env.define(name.clone(), Object::Class(class));
         Ok(Exec::None)
     }
 
@@ -113,23 +347,35 @@ impl Visitor for Interpreter {
 
         match e {
             Expr::Assign(lhs, rhs) => {
+                let place = self.place(lhs)?;
                 let rhs = value!(self.visit_expr(rhs)?);
 
-                if let box Expr::Object(Object::Ident(ident)) = lhs {
-                    self.env.set(&ident, rhs)
-                } else {
-                    Err(Error::UnsupportedOperation(
-                        "Currently only identifiers can be newly assigned.".to_string(),
-                    ))
-                }?;
+                Ok(Exec::Value(self.store(place, rhs)?))
+            }
+            Expr::CompoundAssign(lhs, op, rhs) => {
+                let place = self.place(lhs)?;
+                let current = self.load(&place)?;
+                let rhs = value!(self.visit_expr(rhs)?);
+                let value = apply_binop(current, op.clone(), rhs)?;
 
-                Ok(Exec::None)
+                Ok(Exec::Value(self.store(place, value)?))
+            }
+            Expr::Increment(target, op, fixity) => {
+                let place = self.place(target)?;
+                let current = self.load(&place)?;
+                let value = apply_binop(current.clone(), op.clone(), Object::Int(1))?;
+                self.store(place, value.clone())?;
+
+                match fixity {
+                    Fixity::Prefix => Ok(Exec::Value(value)),
+                    Fixity::Postfix => Ok(Exec::Value(current)),
+                }
             }
-            Expr::Access(_, _) => panic!(),
+            Expr::Access(lhs, name) => Ok(Exec::Value(self.property(lhs, name)?)),
             Expr::Call(p, a) => {
                 // println!("Executing a function");
 
-                let func: Func = self.env.get(p)?.try_into()?;
+                let callee: Object = value!(self.visit_expr(p)?);
                 let mut args = Vec::new();
 
                 for mut arg in a.iter_mut() {
@@ -137,10 +383,75 @@ impl Visitor for Interpreter {
                     args.push(object);
                 }
 
-                let catch = catch!(self.visit_func_call(func.clone(), args.as_mut_slice())?);
+                let catch = catch!(self.call(callee, args.as_mut_slice())?);
                 // println!("Caught: {:?}", catch);
                 Ok(catch)
             },
+            Expr::List(items) => {
+                let mut values = Vec::with_capacity(items.len());
+
+                for item in items {
+                    values.push(value!(self.visit_expr(item)?));
+                }
+
+                Ok(Exec::Value(list::new(values)))
+            }
+            Expr::Map(entries) => {
+                let mut values = IndexMap::with_capacity(entries.len());
+
+                for (key, value) in entries {
+                    let key = Key::new(value!(self.visit_expr(key)?))?;
+                    values.insert(key, value!(self.visit_expr(value)?));
+                }
+
+                Ok(Exec::Value(map::new(values)))
+            }
+            Expr::Index(container, index) => {
+                let container = value!(self.visit_expr(container)?);
+                let index = value!(self.visit_expr(index)?);
+
+                match container {
+                    Object::List(list) => Ok(Exec::Value(list::get(&list, &index)?)),
+                    Object::Map(map) => Ok(Exec::Value(map::get(&map, index)?)),
+                    other => Err(Error::NotIndexable(other.type_name())),
+                }
+            }
+            Expr::Slice(list, start, end) => {
+                let list = value!(self.visit_expr(list)?);
+                let start = match start {
+                    Some(start) => Some(value!(self.visit_expr(start)?)),
+                    None => None,
+                };
+                let end = match end {
+                    Some(end) => Some(value!(self.visit_expr(end)?)),
+                    None => None,
+                };
+
+                match list {
+                    Object::List(list) => Ok(Exec::Value(list::slice(&list, start, end)?)),
+                    other => Err(Error::NotIndexable(other.type_name())),
+                }
+            }
+            Expr::Interpolation(parts) => {
+                let mut text = String::new();
+
+                for part in parts {
+                    let value: Object = value!(self.visit_expr(part)?);
+                    text.push_str(&value.to_string());
+                }
+
+                Ok(Exec::Value(Object::Str(text)))
+            }
+            Expr::Lambda(params, body) => {
+                let lambda = UserFn::new(
+                    Ident("anonymous".into()),
+                    params.clone(),
+                    self.env.capture(),
+                    body.clone(),
+                );
+
+                Ok(Exec::Value(Object::Func(Rc::new(RefCell::new(Box::new(lambda))))))
+            }
             Expr::Object(l) => {
                 if let Object::Ident(ident) = l {
                     Ok(Exec::Value(self.env.get(&ident)?))
@@ -151,8 +462,11 @@ impl Visitor for Interpreter {
             Expr::UnOp(op, rhs) => {
                 let rhs = value!(self.visit_expr(rhs)?);
                 match rhs {
+                    // `!` is logical for every type, `~` is the bitwise not
+                    _ if *op == UnOp::Not => Ok(Object::from(!rhs.is_truthy()?)),
                     Object::Int(_) => exec_unop::<isize>(op.clone(), rhs),
-                    Object::Float(_) => exec_unop::<f32>(op.clone(), rhs),
+                    Object::BigInt(_) => exec_unop::<BigInt>(op.clone(), rhs),
+                    Object::Float(_) => exec_unop::<f64>(op.clone(), rhs),
                     Object::Bool(_) => exec_unop::<bool>(op.clone(), rhs),
                     _ => Err(Error::InvalidUnaryOperator(op.clone(), rhs.to_string())),
                 }.map(Into::into)
@@ -176,17 +490,7 @@ impl Visitor for Interpreter {
                 };
 
                 let rhs = value!(self.visit_expr(rhs)?);
-
-                match lhs {
-                    Object::Int(_) => exec_binop::<isize>(lhs, op.clone(), rhs),
-                    Object::Float(_) => exec_binop::<f32>(lhs, op.clone(), rhs),
-                    Object::Bool(_) => exec_binop::<bool>(lhs, op.clone(), rhs),
-                    _ => Err(Error::InvalidBinaryOperator(
-                        lhs.to_string(),
-                        op.clone(),
-                        rhs.to_string(),
-                    )),
-                }.map(Into::into)
+                apply_binop(lhs, op.clone(), rhs).map(Into::into)
             }
         }
     }
@@ -206,6 +510,19 @@ impl Visitor for Interpreter {
         }
     }
 
+    fn visit_program(&mut self, p: &mut Program) -> Result<Self::Output, Error> {
+        let mut last = Self::Output::default();
+
+        for decl in p.decls.iter_mut() {
+            last = match self.visit_decl(decl)? {
+                e @ Exec::Break(_) | e @ Exec::Continue(_) => return Err(escaped_loop(e)),
+                other => other,
+            };
+        }
+
+        Ok(last)
+    }
+
     fn visit_block(&mut self, block: &mut Block) -> Result<Self::Output, Error> {
         // println!("[BLOCK] {:?}", block);
 
@@ -214,6 +531,10 @@ impl Visitor for Interpreter {
         let mut last = Self::Output::default();
         for decl in &mut block.0 {
             last = self.visit_decl(decl)?;
+
+            if let Exec::Return(_) | Exec::Break(_) | Exec::Continue(_) = last {
+                break;
+            }
         }
 
         self.env.pop_scope();
@@ -221,11 +542,82 @@ impl Visitor for Interpreter {
         Ok(last)
     }
 
-    fn visit_while(&mut self, pred: &mut Expr, block: &mut Block) -> Result<Self::Output, Error> {
+    fn visit_while(
+        &mut self,
+        label: &mut Option<Ident>,
+        pred: &mut Expr,
+        block: &mut Block,
+        inc: &mut Option<Expr>,
+    ) -> Result<Self::Output, Error> {
         let mut last = Self::Output::default();
 
         while value!(self.visit_expr(pred)?).is_truthy()? {
-            last = self.visit_block(block)?;
+            match self.visit_block(block)? {
+                Exec::Break(target) if targets(label, &target) => break,
+                Exec::Continue(target) if targets(label, &target) => (),
+                r @ Exec::Return(_) | r @ Exec::Break(_) | r @ Exec::Continue(_) => return Ok(r),
+                other => last = other,
+            }
+
+            // A `for` loop's increment runs after the body, even when it `continue`s
+            if let Some(inc) = inc {
+                self.visit_expr(inc)?;
+            }
+        }
+
+        Ok(last)
+    }
+
+    fn visit_do_while(
+        &mut self,
+        label: &mut Option<Ident>,
+        block: &mut Block,
+        pred: &mut Expr,
+    ) -> Result<Self::Output, Error> {
+        let mut last = Self::Output::default();
+
+        loop {
+            match self.visit_block(block)? {
+                Exec::Break(target) if targets(label, &target) => break,
+                Exec::Continue(target) if targets(label, &target) => (),
+                r @ Exec::Return(_) | r @ Exec::Break(_) | r @ Exec::Continue(_) => return Ok(r),
+                other => last = other,
+            }
+
+            if !value!(self.visit_expr(pred)?).is_truthy()? {
+                break;
+            }
+        }
+
+        Ok(last)
+    }
+
+    fn visit_for_in(
+        &mut self,
+        label: &mut Option<Ident>,
+        item: &mut Ident,
+        iterable: &mut Expr,
+        block: &mut Block,
+    ) -> Result<Self::Output, Error> {
+        let iterable = value!(self.visit_expr(iterable)?);
+        let iter = iter::iterate(self, iterable)?;
+        let mut last = Self::Output::default();
+
+        while iter::has_next(self, &iter)? {
+            let next = iter::next(self, &iter)?;
+
+            // Each pass gets its own `item`, so closures made in the body keep theirs
+            self.env.push_scope();
+            self.env.define(item.clone(), next);
+            let result = self.visit_block(block);
+            self.env.pop_scope();
+
+            match result? {
+                Exec::Break(target) if targets(label, &target) => break,
+                Exec::Continue(target) if targets(label, &target) => (),
+                r @ Exec::Return(_) | r @ Exec::Break(_) | r @ Exec::Continue(_) => return Ok(r),
+                other => last = other,
+            }
         }
 
         Ok(last)
@@ -239,7 +631,7 @@ impl Visitor for Interpreter {
         let value: Object = if let Some(init) = init {
             value!(self.visit_expr(init)?)
         } else {
-            Object::Unit
+            Object::Nil
         };
 
         self.env.define(ident.clone(), value);
@@ -253,29 +645,169 @@ impl Visitor for Interpreter {
             Stmt::Print(e) => {
                 let v = self.visit_expr(e)?;
                 println!("{}", value!(v));
-                Ok(Object::Unit.into())
+                Ok(Object::Nil.into())
             }
             Stmt::Return(e) => {
                 let value = if let Some(e) = e {
                     value!(self.visit_expr(e)?)
                 } else {
-                    Object::Unit
+                    Object::Nil
                 };
 
                 // println!("Returning: {:?}", value);
 
                 Ok(Exec::Return(value))
             }
+            Stmt::Yield(e) => {
+                let value = value!(self.visit_expr(e)?);
+                let yielder = self.yielder.ok_or(Error::YieldOutsideGenerator)?;
+
+                // SAFETY: `yielder` is only set while `run_generator` borrows it
+                unsafe { (*yielder).suspend(value) };
+                Ok(Exec::None)
+            }
             Stmt::VarDecl(ident, init) => self.visit_var_decl(ident, init),
             Stmt::Block(decls) => self.visit_block(decls),
-            Stmt::Expr(e) => self.visit_expr(e),
+            // The value is discarded, so a body ending in `x = 1;` still returns nil
+            Stmt::Expr(e) => {
+                value!(self.visit_expr(e)?);
+                Ok(Exec::None)
+            }
             Stmt::If(c, g, b) => self.visit_if(c, g, b),
-            Stmt::While(pred, block) => self.visit_while(pred, block), // _ => Ok(Object::Unit),
+            Stmt::While(label, pred, block, inc) => self.visit_while(label, pred, block, inc), // _ => Ok(Object::Nil),
+            Stmt::DoWhile(label, block, pred) => self.visit_do_while(label, block, pred),
+            Stmt::ForIn(label, item, iterable, block) => self.visit_for_in(label, item, iterable, block),
+            Stmt::Break(label) => Ok(Exec::Break(label.clone())),
+            Stmt::Continue(label) => Ok(Exec::Continue(label.clone())),
             Stmt::Func(name, func) => self.visit_func(name, func.clone()),
+            Stmt::Class(name, superclass, methods) => self.visit_class(name, superclass, methods),
         }
     }
 }
 
+/// Whether a `break` or `continue` aimed at `target` applies to the loop labeled `label`.
+fn targets(label: &Option<Ident>, target: &Option<Ident>) -> bool {
+    target.is_none() || target == label
+}
+
+/// The error for a `break` or `continue` that escaped every enclosing loop.
+fn escaped_loop(exec: Exec) -> Error {
+    match exec {
+        Exec::Break(None) => Error::BreakOutsideLoop,
+        Exec::Continue(None) => Error::ContinueOutsideLoop,
+        Exec::Break(Some(label)) | Exec::Continue(Some(label)) => Error::UndefinedLabel(label),
+        _ => unreachable!(),
+    }
+}
+
+/// Whether `lhs == rhs` in Lox, or an error if they can't be compared.
+pub(crate) fn values_equal(lhs: &Object, rhs: &Object) -> Result<bool, Error> {
+    apply_binop(lhs.clone(), BinOp::EqEq, rhs.clone())?.is_truthy()
+}
+
+/// Whether `lhs < rhs` in Lox, or an error if they can't be ordered.
+pub(crate) fn values_less(lhs: &Object, rhs: &Object) -> Result<bool, Error> {
+    apply_binop(lhs.clone(), BinOp::Lt, rhs.clone())?.is_truthy()
+}
+
+/// Apply a non-short-circuiting binary operator to two values.
+fn apply_binop(lhs: Object, op: BinOp, rhs: Object) -> Result<Object, Error> {
+    // `nil` is only ever equal to itself
+    if lhs == Object::Nil || rhs == Object::Nil {
+        match op {
+            BinOp::EqEq => return Ok(Object::from(lhs == rhs)),
+            BinOp::NotEq => return Ok(Object::from(lhs != rhs)),
+            _ => (),
+        }
+    }
+
+    // Instances, classes, functions and iterators are only equal to themselves
+    if let Some(same) = identical(&lhs, &rhs) {
+        match op {
+            BinOp::EqEq => return Ok(Object::from(same)),
+            BinOp::NotEq => return Ok(Object::from(!same)),
+            _ => (),
+        }
+    }
+
+    match op {
+        BinOp::Range => return range::new(&lhs, &rhs, false),
+        BinOp::RangeInclusive => return range::new(&lhs, &rhs, true),
+        BinOp::Step => return range::step(&lhs, &rhs),
+        _ => (),
+    }
+
+    if let Some(result) = operator::big_float_binop(&lhs, op.clone(), &rhs) {
+        return result;
+    }
+
+    let (lhs, rhs) = promote(lhs, rhs);
+
+    if let (Object::List(l), Object::List(r)) = (&lhs, &rhs) {
+        return list::binop(l, op, r);
+    }
+
+    if let (Object::Map(l), Object::Map(r)) = (&lhs, &rhs) {
+        return map::binop(l, op, r);
+    }
+
+    if let (Object::Set(l), Object::Set(r)) = (&lhs, &rhs) {
+        return set::binop(l, op, r);
+    }
+
+    if let (Object::Range(l), Object::Range(r)) = (&lhs, &rhs) {
+        return range::binop(l, op, r);
+    }
+
+    // Values of different types are never equal, and can't be combined
+    if lhs.type_name() != rhs.type_name() {
+        return match op {
+            BinOp::EqEq => Ok(Object::from(false)),
+            BinOp::NotEq => Ok(Object::from(true)),
+            _ => Err(Error::MismatchedOperands(op, lhs.type_name(), rhs.type_name())),
+        };
+    }
+
+    match lhs {
+        Object::Int(_) => exec_binop::<isize>(lhs, op, rhs),
+        Object::BigInt(_) => exec_binop::<BigInt>(lhs, op, rhs),
+        Object::Float(_) => exec_binop::<f64>(lhs, op, rhs),
+        Object::Bool(_) => exec_binop::<bool>(lhs, op, rhs),
+        Object::Str(_) => exec_binop::<String>(lhs, op, rhs),
+        _ => Err(Error::InvalidBinaryOperator(
+            lhs.to_string(),
+            op,
+            rhs.to_string(),
+        )),
+    }
+}
+
+/// Whether `lhs` and `rhs` are the same object, for the types compared by
+/// identity rather than by value.
+fn identical(lhs: &Object, rhs: &Object) -> Option<bool> {
+    match (lhs, rhs) {
+        (Object::Instance(l), Object::Instance(r)) => Some(Rc::ptr_eq(l, r)),
+        (Object::Class(l), Object::Class(r)) => Some(Rc::ptr_eq(l, r)),
+        (Object::Func(l), Object::Func(r)) => Some(Rc::ptr_eq(l, r)),
+        (Object::Iter(l), Object::Iter(r)) => Some(Rc::ptr_eq(l, r)),
+        _ => None,
+    }
+}
+
+/// Mixed int and float operands are both treated as floats, and mixed int and
+/// big int operands as big ints.
+fn promote(lhs: Object, rhs: Object) -> (Object, Object) {
+    match (lhs, rhs) {
+        (Object::Int(l), Object::Float(r)) => (Object::Float(l as f64), Object::Float(r)),
+        (Object::Float(l), Object::Int(r)) => (Object::Float(l), Object::Float(r as f64)),
+        (Object::BigInt(l), Object::Float(r)) => (Object::Float(l.to_f64().unwrap()), Object::Float(r)),
+        (Object::Float(l), Object::BigInt(r)) => (Object::Float(l), Object::Float(r.to_f64().unwrap())),
+        (Object::Int(l), r @ Object::BigInt(_)) => (Object::BigInt(l.into()), r),
+        (l @ Object::BigInt(_), Object::Int(r)) => (l, Object::BigInt(r.into())),
+        other => other,
+    }
+}
+
 fn exec_binop<T: BinaryOp + TryFrom<Object, Error = Error> + ToString>(
     lhs: Object,
     op: BinOp,
@@ -294,12 +826,20 @@ fn exec_unop<T: UnaryOp + TryFrom<Object, Error = Error> + ToString>(
 }
 
 
+/// Somewhere an assignment can store a value.
+enum Place {
+    Var(Ident),
+    Field(Instance, Ident),
+    Index(List, Object),
+    Key(Map, Key),
+}
+
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub enum Exec {
     Return(Object),
     Value(Object),
-    Continue,
-    Break,
+    Continue(Option<Ident>),
+    Break(Option<Ident>),
     None
 }
 
@@ -1,4 +1,4 @@
-#![feature(box_patterns, slice_patterns, specialization)]
+#![feature(slice_patterns, specialization)]
 
 use std::borrow::Borrow;
 use std::fs;
@@ -8,10 +8,12 @@ use std::path::{Path, PathBuf};
 use structopt::StructOpt;
 
 pub(crate) mod ast;
+pub(crate) mod builtins;
 // pub(crate) mod ast_rewrite;
 pub mod error;
 pub(crate) mod interpreter;
 pub(crate) mod parser;
+pub(crate) mod tester;
 pub(crate) mod token;
 // pub(crate) mod visitor;
 pub(crate) mod env;
@@ -22,6 +24,9 @@ use crate::error::Error;
 use crate::interpreter::Interpreter;
 use crate::parser::LoxParser;
 
+pub use crate::interpreter::STACK_SIZE;
+pub use crate::parser::Dialect;
+
 /// A Lox program.
 pub struct Lox;
 
@@ -37,9 +42,7 @@ impl Lox {
     pub fn run<C: Borrow<str>>(input: C, config: &Config, interpreter: &mut Interpreter) -> Result<(), Error> {
         let code = input.borrow();
 
-        let pairs = LoxParser::parse_str(code)
-            .map_err(|e| eprintln!("{:#?}", e))
-            .unwrap();
+        let pairs = LoxParser::parse_str(code, config.dialect)?;
 
         if config.parse_tree {
             println!("{:#?}", pairs);
@@ -52,14 +55,16 @@ impl Lox {
             printer.visit_program(&mut ast)?;
         }
 
-        match interpreter.visit_program(&mut ast) {
-            Ok(_) => (),
-            Err(e) => println!("Error: {}", e),
-        }
+        interpreter.visit_program(&mut ast)?;
 
         Ok(())
     }
 
+    /// Run every `.lox` file under `path` and check it against its `// expect` comments
+    pub fn run_tests<P: AsRef<Path>>(path: P, config: &Config) -> Result<bool, Error> {
+        tester::run_tests(path.as_ref(), config)
+    }
+
     /// Start the Lox REPL
     pub fn run_prompt(config: &Config) -> Result<(), Error> {
         let stdin = stdin();
@@ -70,7 +75,9 @@ impl Lox {
         stdout().flush()?;
 
         while let Some(line) = lines.next().transpose()? {
-            Lox::run(line, config, &mut interpreter)?;
+            if let Err(e) = Lox::run(line, config, &mut interpreter) {
+                eprintln!("Error: {}", e);
+            }
 
             print!("> ");
             stdout().flush()?;
@@ -93,4 +100,20 @@ pub struct Config {
     /// Print the HIR AST
     #[structopt(short = "a", long = "emit-ast")]
     pub emit_ast: bool,
+    /// The Lox syntax to accept: `brace` or `standard`
+    #[structopt(long = "dialect", default_value = "brace")]
+    pub dialect: Dialect,
+    #[structopt(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Clone, StructOpt)]
+pub enum Command {
+    /// Run .lox files and compare their output with `// expect: ...` comments
+    #[structopt(name = "test")]
+    Test {
+        /// A .lox file or a directory to search for them
+        #[structopt(parse(from_os_str))]
+        path: PathBuf,
+    },
 }
\ No newline at end of file
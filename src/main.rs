@@ -1,18 +1,41 @@
 use lox::Lox;
-use lox::Config;
+use lox::{Command, Config, STACK_SIZE};
 
+use std::process;
+use std::thread;
 
 use structopt::StructOpt;
-pub mod parser;
 
 fn main() {
+    // Lox calls recurse on the native stack, so run on one big enough to hit
+    // the call depth limit first
+    let child = thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(run)
+        .expect("failed to spawn the interpreter thread");
+
+    if child.join().is_err() {
+        process::exit(101);
+    }
+}
+
+fn run() {
     let args = Config::from_args();
 
-    let result = if let Some(ref path) = &args.path {
+    let result = if let Some(Command::Test { path }) = &args.command {
+        Lox::run_tests(path, &args).map(|passed| {
+            if !passed {
+                process::exit(1);
+            }
+        })
+    } else if let Some(ref path) = &args.path {
         Lox::run_file(path, &args)
     } else {
         Lox::run_prompt(&args)
     };
 
-    result.map_err(|e| eprintln!("{}", e)).unwrap();
+    if let Err(e) = result {
+        eprintln!("Error: {}", e);
+        process::exit(e.exit_code());
+    }
 }
@@ -1,12 +1,219 @@
-use pest::{error::Error, iterators::Pairs, Parser};
+use pest::{
+    error::{Error, ErrorVariant},
+    iterators::{Pair, Pairs},
+    Parser, Span,
+};
 use pest_derive::Parser;
 
+use crate::ast::literal;
+use crate::ast::operator::is_compound_assign;
+
+use std::fmt;
+use std::str::FromStr;
+
 #[derive(Debug, Copy, Clone, Parser)]
 #[grammar = "lox.pest"]
 pub struct LoxParser;
 
+/// The flavor of Lox syntax to accept.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum Dialect {
+    /// `if cond { ... }`: bare conditions and mandatory braces.
+    #[default]
+    Brace,
+    /// `if (cond) stmt;`: the syntax from Crafting Interpreters.
+    Standard,
+}
+
+impl FromStr for Dialect {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "brace" => Ok(Dialect::Brace),
+            "standard" => Ok(Dialect::Standard),
+            other => Err(format!(
+                "unknown dialect `{}`, expected `brace` or `standard`",
+                other
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Dialect {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Dialect::Brace => write!(f, "brace"),
+            Dialect::Standard => write!(f, "standard"),
+        }
+    }
+}
+
 impl LoxParser {
-    pub fn parse_str(input: &str) -> Result<Pairs<Rule>, Error<Rule>> {
-        LoxParser::parse(Rule::program, input)
+    pub fn parse_str(input: &str, dialect: Dialect) -> Result<Pairs<Rule>, Error<Rule>> {
+        let pairs = LoxParser::parse(Rule::program, input)?;
+        check_dialect(pairs.clone(), dialect)?;
+        check_literals(pairs.clone())?;
+        check_targets(pairs.clone())?;
+        Ok(pairs)
+    }
+}
+
+/// The grammar accepts both dialects' `if` and `while` forms, so reject the ones
+/// that don't belong to `dialect`.
+fn check_dialect(pairs: Pairs<Rule>, dialect: Dialect) -> Result<(), Error<Rule>> {
+    for pair in pairs.flatten() {
+        match (pair.as_rule(), dialect) {
+            (Rule::if_stmt, Dialect::Standard) | (Rule::while_stmt, Dialect::Standard) => {
+                let cond = pair.clone().into_inner().next().unwrap();
+
+                if cond.as_rule() != Rule::paren_cond {
+                    return Err(error_at(
+                        cond.as_span(),
+                        "Expected `(` around the condition in the standard dialect",
+                    ));
+                }
+            }
+            (Rule::if_stmt, Dialect::Brace)
+            | (Rule::while_stmt, Dialect::Brace)
+            | (Rule::for_stmt, Dialect::Brace)
+            | (Rule::for_in_stmt, Dialect::Brace) => {
+                let unbraced = pair.clone().into_inner().find(|body| {
+                    body.as_rule() == Rule::statement
+                        && body.clone().into_inner().next().unwrap().as_rule() != Rule::block
+                });
+
+                if let Some(body) = unbraced {
+                    return Err(error_at(
+                        body.as_span(),
+                        "Expected a `{ ... }` block, bodies without braces need `--dialect=standard`",
+                    ));
+                }
+            }
+            _ => (),
+        }
+    }
+
+    Ok(())
+}
+
+/// Literals the grammar accepts but can't be decoded, like a string with an
+/// unknown escape or a float too large to represent.
+fn check_literals(pairs: Pairs<Rule>) -> Result<(), Error<Rule>> {
+    for pair in pairs.flatten() {
+        match pair.as_rule() {
+            Rule::string => {
+                literal::parse_string(&pair)?;
+            }
+            Rule::int => {
+                literal::parse_int(&pair)?;
+            }
+            Rule::float => {
+                literal::parse_float(&pair)?;
+            }
+            _ => (),
+        }
+    }
+
+    Ok(())
+}
+
+/// Assignments and `++`/`--` need a variable or property to store into.
+fn check_targets(pairs: Pairs<Rule>) -> Result<(), Error<Rule>> {
+    for pair in pairs.flatten() {
+        match pair.as_rule() {
+            // `term (op term)*`. Assignment binds loosest and to the right, so
+            // each one's target is everything back to the previous assignment.
+            Rule::expr => {
+                let items: Vec<Pair<Rule>> = pair.into_inner().collect();
+                let mut target = 0;
+
+                for (i, item) in items.iter().enumerate() {
+                    if !is_assignment(item.as_rule()) {
+                        continue;
+                    }
+
+                    if i - target != 1 || !is_assignable(&items[target]) {
+                        let span = items[target]
+                            .as_span()
+                            .start_pos()
+                            .span(&items[i - 1].as_span().end_pos());
+
+                        return Err(error_at(span, "Invalid assignment target"));
+                    }
+
+                    target = i + 1;
+                }
+            }
+            // `op_unary* value postfix*`
+            Rule::term => {
+                let items: Vec<Pair<Rule>> = pair.into_inner().collect();
+                let value = items.iter().position(|p| p.as_rule() == Rule::value).unwrap();
+                let assignable = |chain: &[Pair<Rule>]| match chain.split_last() {
+                    Some((last, _)) => is_place(last),
+                    None => is_ident(&items[value]),
+                };
+
+                for (i, item) in items.iter().enumerate() {
+                    if !is_increment(item.as_rule()) {
+                        continue;
+                    }
+
+                    let valid = if i < value {
+                        // A prefix `++` applies to the whole postfix chain, and must
+                        // be the innermost unary operator
+                        i + 1 == value && assignable(&items[value + 1..])
+                    } else {
+                        assignable(&items[value + 1..i])
+                    };
+
+                    if !valid {
+                        return Err(error_at(item.as_span(), "Invalid increment target"));
+                    }
+                }
+            }
+            _ => (),
+        }
     }
+
+    Ok(())
+}
+
+fn is_assignment(rule: Rule) -> bool {
+    rule == Rule::op_assign || is_compound_assign(rule)
+}
+
+fn is_increment(rule: Rule) -> bool {
+    rule == Rule::op_increment || rule == Rule::op_decrement
+}
+
+/// A `term` that is a variable or ends in a property access or index.
+fn is_assignable(term: &Pair<Rule>) -> bool {
+    let items: Vec<Pair<Rule>> = term.clone().into_inner().collect();
+
+    match items.split_first() {
+        Some((value, postfix)) if value.as_rule() == Rule::value => match postfix.last() {
+            Some(last) => is_place(last),
+            None => is_ident(value),
+        },
+        _ => false,
+    }
+}
+
+/// A postfix operator that can be assigned through, `.field` or `[index]`.
+fn is_place(postfix: &Pair<Rule>) -> bool {
+    postfix.as_rule() == Rule::access || postfix.as_rule() == Rule::index
+}
+
+fn is_ident(value: &Pair<Rule>) -> bool {
+    value.clone().into_inner().next().unwrap().as_rule() == Rule::ident
+}
+
+fn error_at(span: Span, message: &str) -> Error<Rule> {
+    Error::new_from_span(
+        ErrorVariant::CustomError {
+            message: message.to_string(),
+        },
+        span,
+    )
 }
@@ -0,0 +1,204 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use pest::error::{Error as PestError, LineColLocation};
+
+use crate::error::Error;
+use crate::parser::{Dialect, LoxParser, Rule};
+use crate::Config;
+
+/// What a test file says should happen when it runs, read from comments:
+///
+/// - `// expect: value` a line `print`ed by the program
+/// - `// expect runtime error: message` the program stops with this error
+/// - `// [line N] Error: message` or `// Error: message` a syntax error on line
+///   `N` (or the comment's own line), with this message
+/// - `// dialect: standard` the dialect to parse the file in, instead of the
+///   one `lox test` was given
+#[derive(Debug, Default, PartialEq)]
+pub struct Expectations {
+    pub output: Vec<String>,
+    pub runtime_error: Option<String>,
+    pub syntax_error: Option<(usize, String)>,
+    pub dialect: Option<Dialect>,
+}
+
+impl Expectations {
+    pub fn from_source(source: &str) -> Self {
+        let mut expectations = Expectations::default();
+
+        for (i, line) in source.lines().enumerate() {
+            // Markers may follow code containing `//`, like `print "a//b"; // expect: a//b`
+            if let Some(start) = line.find("// expect:") {
+                let value = &line[start + "// expect:".len()..];
+                expectations.output.push(value.trim().to_string());
+            } else if let Some(start) = line.find("// expect runtime error:") {
+                let message = &line[start + "// expect runtime error:".len()..];
+                expectations.runtime_error = Some(message.trim().to_string());
+            } else if let Some(start) = line.find("// [line ") {
+                let rest = &line[start + "// [line ".len()..];
+
+                if let Some((number, error)) = rest.split_once(']') {
+                    if let (Ok(number), Some(message)) = (number.parse(), syntax_message(error)) {
+                        expectations.syntax_error = Some((number, message));
+                    }
+                }
+            } else if let Some(start) = line.find("// Error") {
+                let message = syntax_message(&line[start + "//".len()..]);
+                expectations.syntax_error = Some((i + 1, message.unwrap_or_default()));
+            } else if let Some(start) = line.find("// dialect:") {
+                expectations.dialect = line[start + "// dialect:".len()..].trim().parse().ok();
+            }
+        }
+
+        expectations
+    }
+}
+
+/// The message in `Error: message`, or in `Error at 'x': message`.
+fn syntax_message(text: &str) -> Option<String> {
+    let rest = text.trim_start().strip_prefix("Error")?;
+    let message = rest.find(':').map_or("", |colon| &rest[colon + 1..]);
+    Some(message.trim().to_string())
+}
+
+/// How a test program finished.
+#[derive(Debug, PartialEq)]
+pub enum Outcome {
+    Ok,
+    SyntaxError(usize, String),
+    RuntimeError(String),
+    /// The interpreter panicked, aborted or was killed instead of reporting an error.
+    Crash(String),
+}
+
+fn syntax_error(error: &PestError<Rule>) -> Outcome {
+    let line = match error.line_col {
+        LineColLocation::Pos((line, _)) | LineColLocation::Span((line, _), _) => line,
+    };
+
+    Outcome::SyntaxError(line, error.variant.message().to_string())
+}
+
+/// Run the test at `path` in a child `lox` process, so that not even a stack
+/// overflow can take down the rest of the suite.
+pub fn run_file(path: &Path, source: &str, dialect: Dialect) -> Result<(Vec<String>, Outcome), Error> {
+    // Syntax errors are found here, where their line and message are at hand
+    if let Err(e) = LoxParser::parse_str(source, dialect) {
+        return Ok((Vec::new(), syntax_error(&e)));
+    }
+
+    let run = Command::new(env::current_exe()?)
+        .arg("--dialect")
+        .arg(dialect.to_string())
+        .arg("--file")
+        .arg(path)
+        .output()?;
+
+    let output = String::from_utf8_lossy(&run.stdout)
+        .lines()
+        .map(str::to_string)
+        .collect();
+    let stderr = String::from_utf8_lossy(&run.stderr).trim().to_string();
+
+    let outcome = match (run.status.code(), stderr.strip_prefix("Error: ")) {
+        (Some(0), _) => Outcome::Ok,
+        (Some(70), Some(message)) => Outcome::RuntimeError(message.to_string()),
+        (Some(code), _) => Outcome::Crash(format!("exited with code {}: {}", code, stderr)),
+        (None, _) => Outcome::Crash(format!("killed by a signal: {}", stderr)),
+    };
+
+    Ok((output, outcome))
+}
+
+/// Compare a finished run against its expectations, returning every mismatch.
+pub fn check(expected: &Expectations, output: &[String], outcome: &Outcome) -> Vec<String> {
+    let mut failures = Vec::new();
+
+    for (i, expected) in expected.output.iter().enumerate() {
+        match output.get(i) {
+            Some(actual) if actual == expected => (),
+            Some(actual) => failures.push(format!("expected output `{}`, got `{}`", expected, actual)),
+            None => failures.push(format!("expected output `{}`, got nothing", expected)),
+        }
+    }
+
+    for extra in output.iter().skip(expected.output.len()) {
+        failures.push(format!("unexpected output `{}`", extra));
+    }
+
+    match (outcome, &expected.syntax_error, &expected.runtime_error) {
+        (Outcome::Crash(message), _, _) => failures.push(format!("interpreter crashed: {}", message)),
+        (Outcome::SyntaxError(line, message), Some(expected), _) if (line, message) == (&expected.0, &expected.1) => (),
+        (Outcome::RuntimeError(message), None, Some(expected)) if message == expected => (),
+        (Outcome::Ok, None, None) => (),
+        (_, Some((line, message)), _) => failures.push(format!(
+            "expected a syntax error on line {}: `{}`, got {:?}",
+            line, message, outcome
+        )),
+        (_, None, Some(message)) => {
+            failures.push(format!("expected runtime error `{}`, got {:?}", message, outcome))
+        }
+        (_, None, None) => failures.push(format!("expected success, got {:?}", outcome)),
+    }
+
+    failures
+}
+
+/// Every `.lox` file at `path`, recursively and in a stable order.
+pub fn collect_tests(path: &Path) -> Result<Vec<PathBuf>, Error> {
+    if path.is_file() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let mut tests = Vec::new();
+    let mut entries: Vec<PathBuf> = fs::read_dir(path)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<_, _>>()?;
+    entries.sort();
+
+    for entry in entries {
+        if entry.is_dir() {
+            tests.extend(collect_tests(&entry)?);
+        } else if entry.extension().is_some_and(|ext| ext == "lox") {
+            tests.push(entry);
+        }
+    }
+
+    Ok(tests)
+}
+
+/// Run every test at `path`, print a report, and return whether they all passed.
+pub fn run_tests(path: &Path, config: &Config) -> Result<bool, Error> {
+    let tests = collect_tests(path)?;
+    let mut failed = 0;
+
+    for test in &tests {
+        let failures = match fs::read_to_string(test) {
+            Ok(source) => {
+                let expected = Expectations::from_source(&source);
+                let dialect = expected.dialect.unwrap_or(config.dialect);
+
+                match run_file(test, &source, dialect) {
+                    Ok((output, outcome)) => check(&expected, &output, &outcome),
+                    Err(e) => vec![format!("could not run the test: {}", e)],
+                }
+            }
+            Err(e) => vec![format!("could not read file: {}", e)],
+        };
+
+        if !failures.is_empty() {
+            failed += 1;
+            println!("FAIL {}", test.display());
+
+            for failure in failures {
+                println!("    {}", failure);
+            }
+        }
+    }
+
+    println!("{} passed, {} failed", tests.len() - failed, failed);
+    Ok(failed == 0)
+}